@@ -213,6 +213,30 @@ export const MARKETPLACE_ABI = [
     outputs: [{ name: "", type: "uint256" }],
     stateMutability: "view",
   },
+  {
+    type: "function",
+    name: "getTokenCollectionInfo",
+    inputs: [
+      { name: "nftContract", type: "address" },
+      { name: "tokenId", type: "uint256" },
+    ],
+    outputs: [
+      {
+        name: "",
+        type: "tuple",
+        internalType: "struct CollectionInfo",
+        components: [
+          { name: "source", type: "uint8" },
+          { name: "collectionId", type: "uint256" },
+          { name: "name", type: "string" },
+          { name: "symbol", type: "string" },
+          { name: "creator", type: "address" },
+          { name: "contractURI", type: "string" },
+        ],
+      },
+    ],
+    stateMutability: "view",
+  },
   {
     type: "event",
     name: "AuctionCreated",
@@ -220,13 +244,36 @@ export const MARKETPLACE_ABI = [
       { name: "auctionId", type: "uint256", indexed: true },
       { name: "nftContract", type: "address", indexed: true },
       { name: "tokenId", type: "uint256", indexed: true },
+      { name: "seller", type: "address", indexed: false },
       { name: "reservePrice", type: "uint256", indexed: false },
+      { name: "duration", type: "uint256", indexed: false },
       { name: "endTime", type: "uint256", indexed: false },
+      { name: "platformFeePercentage", type: "uint256", indexed: false },
     ],
   },
   {
     type: "event",
     name: "BidPlaced",
+    inputs: [
+      { name: "auctionId", type: "uint256", indexed: true },
+      { name: "bidder", type: "address", indexed: true },
+      { name: "previousBidder", type: "address", indexed: true },
+      { name: "amount", type: "uint256", indexed: false },
+      { name: "refund", type: "uint256", indexed: false },
+    ],
+  },
+  {
+    type: "event",
+    name: "OutbidRefundSent",
+    inputs: [
+      { name: "auctionId", type: "uint256", indexed: true },
+      { name: "bidder", type: "address", indexed: true },
+      { name: "amount", type: "uint256", indexed: false },
+    ],
+  },
+  {
+    type: "event",
+    name: "OutbidRefundCredited",
     inputs: [
       { name: "auctionId", type: "uint256", indexed: true },
       { name: "bidder", type: "address", indexed: true },
@@ -239,7 +286,11 @@ export const MARKETPLACE_ABI = [
     inputs: [
       { name: "auctionId", type: "uint256", indexed: true },
       { name: "winner", type: "address", indexed: true },
+      { name: "seller", type: "address", indexed: true },
       { name: "amount", type: "uint256", indexed: false },
+      { name: "sellerProceeds", type: "uint256", indexed: false },
+      { name: "platformFee", type: "uint256", indexed: false },
+      { name: "nftRecipient", type: "address", indexed: false },
     ],
   },
 ] as const;
@@ -11,32 +11,218 @@ interface INeonMarketplace  {
 
     function createAuction(address nft_contract, uint256 token_id, uint256 reserve_price, uint256 duration) external returns (uint256);
 
+    function createAuctions(address nft_contract, uint256[] memory token_ids, uint256[] memory reserve_prices, uint256[] memory durations) external returns (uint256[] memory);
+
+    function createAuctionWithCreator(address nft_contract, uint256 token_id, uint256 reserve_price, uint256 duration, address creator, uint256 creator_share_bps) external returns (uint256);
+
+    function createAuctionWithHiddenReserve(address nft_contract, uint256 token_id, uint256 starting_price, uint256 duration, bytes32 reserve_commitment) external returns (uint256);
+
+    function revealReserve(uint256 auction_id, uint256 reserve_price, bytes32 salt) external;
+
+    function createBundleAuction((address,uint256)[] memory items, uint256 reserve_price, uint256 duration) external returns (uint256);
+
     function cancelAuction(uint256 auction_id) external;
 
     function placeBid(uint256 auction_id) external payable;
 
+    function placeBidWithReferrer(uint256 auction_id, address referrer) external payable;
+
+    function placeProxyBid(uint256 auction_id) external payable;
+
+    function placeProxyBidWithReferrer(uint256 auction_id, address referrer) external payable;
+
+    function setAuctionGate(uint256 auction_id, uint8 gate_kind, bytes32 merkle_root, address collection) external;
+
+    function createGatedAuction(address nft_contract, uint256 token_id, uint256 reserve_price, uint256 duration, uint8 gate_kind, bytes32 merkle_root, address collection) external returns (uint256);
+
+    function authorizeBidder(uint256 auction_id, bytes32[] memory proof, bytes calldata signature) external;
+
+    function placeGatedBid(uint256 auction_id, bytes32[] memory proof, bytes calldata signature) external payable;
+
     function settleAuction(uint256 auction_id) external;
 
+    function createListing(address nft_contract, uint256 token_id, uint256 price) external returns (uint256);
+
+    function createErc1155Listing(address nft_contract, uint256 token_id, uint256 amount, uint256 price) external returns (uint256);
+
+    function cancelListing(uint256 listing_id) external;
+
+    function buy(uint256 listing_id) external payable;
+
+    function buyWithReferrer(uint256 listing_id, address referrer) external payable;
+
+    function fulfillOrder(Order order, bytes calldata signature) external payable;
+
+    function cancelOrder(uint256 nonce) external;
+
+    function cancelAllOrders(uint256 min_nonce) external;
+
+    function claimNft(uint256 auction_id) external;
+
+    function refundUndeliverableNft(uint256 auction_id) external;
+
     function updatePlatformFee(uint256 new_fee_percentage) external;
 
+    function executePlatformFeeChange() external;
+
+    function cancelPlatformFeeChange() external;
+
+    function transferPlatformOwnership(address new_owner) external;
+
+    function acceptPlatformOwnership() external;
+
+    function updateFeeRecipient(address new_recipient) external;
+
+    function setFeePayees((address,uint256)[] memory payees) external;
+
+    function setAccountBlocked(address account, bool blocked) external;
+
+    function setDurationBounds(uint256 min_duration, uint256 max_duration) external;
+
+    function setMaxFeePercentage(uint256 max_fee_percentage) external;
+
+    function setMinBidIncrement(uint256 increment_bps) external;
+
+    function setExtensionWindow(uint256 window) external;
+
+    function setTokenBlocked(address nft_contract, uint256 token_id, bool blocked) external;
+
+    function setBidSigner(address signer) external;
+
+    function setReferralShare(uint256 share_bps) external;
+
+    function setCurationEnabled(bool enabled) external;
+
+    function addCollection(address nft_contract) external;
+
+    function removeCollection(address nft_contract) external;
+
+    function setCollectionFee(address nft_contract, uint256 fee_percentage) external;
+
+    function clearCollectionFee(address nft_contract) external;
+
+    function setSellerFee(address seller, uint256 fee_percentage) external;
+
+    function clearSellerFee(address seller) external;
+
+    function pause() external;
+
+    function unpause() external;
+
+    function emergencyCancelAuction(uint256 auction_id) external;
+
+    function forceCancelAuction(uint256 auction_id, uint8 reason) external;
+
     function withdraw() external;
 
+    function withdrawTo(address recipient, uint256 amount) external;
+
     function getAuction(uint256 auction_id) external view returns (address, uint256, address, uint256, uint256, address, uint256, bool);
 
+    function getAuctions(uint256[] memory auction_ids) external view returns (AuctionView[] memory);
+
+    function getAuctionsRange(uint256 start, uint256 count) external view returns (AuctionView[] memory);
+
+    function getAuctionPlatformFee(uint256 auction_id) external view returns (uint256);
+
+    function getListing(uint256 listing_id) external view returns (address, uint256, address, uint256, bool);
+
+    function isListingValid(uint256 listing_id) external view returns (bool);
+
+    function isErc1155Listing(uint256 listing_id) external view returns (bool);
+
+    function getListingAmount(uint256 listing_id) external view returns (uint256);
+
+    function getNextListingId() external view returns (uint256);
+
+    function getDomainSeparator() external view returns (bytes32);
+
+    function hashOrder(Order order) external view returns (bytes32);
+
+    function isOrderNonceValid(address seller, uint256 nonce) external view returns (bool);
+
+    function getMinOrderNonce(address seller) external view returns (uint256);
+
+    function isErc1155Auction(uint256 auction_id) external view returns (bool);
+
+    function getAuctionAmount(uint256 auction_id) external view returns (uint256);
+
+    function isBundleAuction(uint256 auction_id) external view returns (bool);
+
+    function getBundleItems(uint256 auction_id) external view returns (address,uint256)[] memory;
+
+    function getNftClaim(uint256 auction_id) external view returns (address, uint256, bool);
+
     function isAuctionActive(uint256 auction_id) external view returns (bool);
 
+    function reserveMet(uint256 auction_id) external view returns (bool);
+
+    function hashReserve(uint256 reserve_price, bytes32 salt) external view returns (bytes32);
+
+    function isAuctionCancelled(uint256 auction_id) external view returns (bool);
+
+    function isCurationEnabled() external view returns (bool);
+
+    function isCollectionAllowed(address nft_contract) external view returns (bool);
+
+    function getCollectionFee(address nft_contract) external view returns (bool, uint256);
+
+    function getPendingCollectionFee(address nft_contract) external view returns (bool, uint256, uint256);
+
+    function getSellerFee(address seller) external view returns (bool, uint256);
+
+    function getPendingSellerFee(address seller) external view returns (bool, uint256, uint256);
+
+    function effectiveFee(address nft_contract, address seller) external view returns (uint256);
+
     function getBalance(address user_address) external view returns (uint256);
 
     function getNextAuctionId() external view returns (uint256);
 
     function getPlatformFee() external view returns (uint256);
 
+    function getPendingPlatformFee() external view returns (uint256, uint256);
+
+    function getPendingPlatformOwner() external view returns (address);
+
+    function getFeeRecipient() external view returns (address);
+
+    function getAuctionGate(uint256 auction_id) external view returns (uint8, bytes32, address);
+
+    function isBidderAllowed(uint256 auction_id, address bidder) external view returns (bool);
+
+    function isAccountBlocked(address account) external view returns (bool);
+
+    function isTokenBlocked(address nft_contract, uint256 token_id) external view returns (bool);
+
+    function getConfig() external view returns (uint256, uint256, uint256, uint256, uint256);
+
+    function getBidSigner() external view returns (address);
+
+    function hashBidAuthorization(uint256 auction_id, address bidder) external view returns (bytes32);
+
+    function getReferralShare() external view returns (uint256);
+
+    function getAuctionSplits(uint256 auction_id) external view returns (address, address, uint256);
+
+    function getFeePayees() external view returns (address,uint256)[] memory;
+
+    function isPaused() external view returns (bool);
+
     function getPlatformOwner() external view returns (address);
 
-    function getTokenCollectionInfo(address nft_contract, uint256 token_id) external view returns (uint256, string memory, string memory, address);
+    function getTokenCollectionInfo(address nft_contract, uint256 token_id) external view returns (CollectionInfo);
+
+    function getTokenUri(address nft_contract, uint256 token_id) external view returns (string memory);
 
     function getPlatformFeePercentage() external view returns (uint256);
 
+    function onERC1155Received(address operator, address from, uint256 id, uint256 value, bytes calldata data) external returns (bytes4);
+
+    function onERC1155BatchReceived(address operator, address from, uint256[] memory ids, uint256[] memory values, bytes calldata data) external returns (bytes4);
+
+    function supportsInterface(bytes4 interface_id) external view returns (bool);
+
     error AlreadyInitialized();
 
     error AuctionNotFound();
@@ -70,4 +256,86 @@ interface INeonMarketplace  {
     error ERC721InvalidTokenId();
 
     error NotApprovedForTransfer();
+
+    error ContractPaused();
+
+    error ContractNotPaused();
+
+    error NotPendingPlatformOwner();
+
+    error InvalidFeeRecipient();
+
+    error NoPendingFeeChange();
+
+    error FeeChangeTimelocked();
+
+    error NothingToClaim();
+
+    error NotAuctionWinner();
+
+    error RefundNotAvailable();
+
+    error InvalidBundleSize();
+
+    error InvalidAmount();
+
+    error InvalidListingData();
+
+    error InvalidSignature();
+
+    error OrderExpired();
+
+    error OrderNonceInvalid();
+
+    error UnsupportedCurrency();
+
+    error IncorrectPayment();
+
+    error ListingNotFound();
+
+    error ListingNotActive();
+
+    error NotListingSeller();
+
+    error InvalidPrice();
+
+    error Reentrancy();
+
+    error CollectionNotAllowed();
+
+    error InvalidFeePayees();
+
+    error InvalidCreatorShare();
+
+    error InvalidReferralShare();
+
+    error ReserveNotRevealed();
+
+    error InvalidReserveReveal();
+
+    error InvalidGate();
+
+    error BidderNotAllowed();
+
+    error AccountBlocked();
+
+    error TokenBlocked();
+
+    error InvalidBidIncrement();
+
+    error InvalidExtensionWindow();
+
+    error PageTooLarge();
+
+    error InvalidRecipient();
+
+    error InvalidWithdrawalAmount();
+
+    error SellerCannotBid();
+
+    error InvalidDeposit();
+
+    struct AuctionView { uint256 auctionId; address nftContract; uint256 tokenId; address seller; uint256 reservePrice; uint256 currentBid; address currentBidder; uint256 endTime; bool settled; bool isBundle; bool isErc1155; uint256 amount; uint8 status; uint256 minNextBid; uint256 timeRemaining; }
+
+    struct CollectionInfo { uint8 source; uint256 collectionId; string name; string symbol; address creator; string contractURI; }
 }
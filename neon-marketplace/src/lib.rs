@@ -8,15 +8,15 @@ use alloc::string::{String, ToString};
 use alloc::vec;
 use alloc::vec::Vec;
 use stylus_sdk::{
-    alloy_primitives::{Address, U256},
-    call::Call,
-    evm,
+    abi::Bytes,
+    alloy_primitives::{address, Address, FixedBytes, B256, U256, U8},
+    crypto::keccak,
     prelude::*,
 };
-use alloy_sol_types::sol;
+use alloy_sol_types::{sol, SolCall, SolValue};
 
 // ERC721 Interface for interacting with existing NFTs
-sol_interface! {
+sol! {
     interface IERC721 {
         function ownerOf(uint256 tokenId) external view returns (address);
         function transferFrom(address from, address to, uint256 tokenId) external;
@@ -29,8 +29,39 @@ sol_interface! {
     }
 }
 
+// ERC1155 Interface for semi-fungible tokens (editions)
+sol! {
+    interface IERC1155 {
+        function balanceOf(address account, uint256 id) external view returns (uint256);
+        function setApprovalForAll(address operator, bool approved) external;
+        function isApprovedForAll(address account, address operator) external view returns (bool);
+        function safeTransferFrom(address from, address to, uint256 id, uint256 value, bytes calldata data) external;
+    }
+
+    interface IERC1155Receiver {
+        function onERC1155Received(address operator, address from, uint256 id, uint256 value, bytes calldata data) external returns (bytes4);
+    }
+}
+
+// ERC721 Metadata extension and ERC-7572 contract-level metadata (optional)
+sol! {
+    interface IERC721Metadata {
+        function name() external view returns (string memory);
+        function symbol() external view returns (string memory);
+        function tokenURI(uint256 tokenId) external view returns (string memory);
+    }
+
+    interface IERC7572 {
+        function contractURI() external view returns (string memory);
+    }
+
+    interface IERC165 {
+        function supportsInterface(bytes4 interfaceId) external view returns (bool);
+    }
+}
+
 // Multi-Collection NFT Interface (optional - for additional collection info)
-sol_interface! {
+sol! {
     interface IMultiCollectionNFT {
         function tokenCollection(uint256 tokenId) external view returns (uint256);
         function getCollection(uint256 collectionId) external view returns (string memory, string memory, address, string memory, uint256);
@@ -38,18 +69,147 @@ sol_interface! {
     }
 }
 
+// Off-chain signed fixed-price order (EIP-712)
+sol! {
+    #[derive(AbiType)]
+    struct Order {
+        address seller;
+        address nftContract;
+        uint256 tokenId;
+        uint256 price;
+        address currency;    // Must be address(0): orders are paid in native ETH only
+        uint256 expiry;
+        uint256 nonce;
+    }
+}
+
+// Auction state returned by the batched views, with derived fields
+sol! {
+    #[derive(AbiType)]
+    struct AuctionView {
+        uint256 auctionId;
+        address nftContract;
+        uint256 tokenId;
+        address seller;
+        uint256 reservePrice;
+        uint256 currentBid;
+        address currentBidder;
+        uint256 endTime;
+        bool settled;
+        bool isBundle;
+        bool isErc1155;
+        uint256 amount;
+        uint8 status;
+        uint256 minNextBid;
+        uint256 timeRemaining;
+    }
+}
+
+// Token collection details returned by get_token_collection_info
+sol! {
+    #[derive(AbiType)]
+    struct CollectionInfo {
+        uint8 source;
+        uint256 collectionId;
+        string name;
+        string symbol;
+        address creator;
+        string contractURI;
+    }
+}
+
+// Lets `export-abi` print the definitions of structs returned by views
+macro_rules! export_struct_abi {
+    ($($name:ident),*) => {
+        $(
+            #[cfg(feature = "export-abi")]
+            impl stylus_sdk::abi::export::internal::InnerTypes for $name {
+                fn inner_types() -> Vec<stylus_sdk::abi::export::internal::InnerType> {
+                    use alloy_sol_types::SolStruct;
+
+                    // "Name(uint256 a,...)" => "struct Name { uint256 a; ... }"
+                    let encode_type = <$name as SolStruct>::eip712_encode_type();
+                    let fields = encode_type.trim_start_matches(concat!(stringify!($name), "(")).trim_end_matches(')');
+                    let body: String = fields.split(',').map(|field| format!(" {};", field)).collect();
+
+                    vec![stylus_sdk::abi::export::internal::InnerType {
+                        name: format!("struct {} {{{} }}", stringify!($name), body),
+                        id: core::any::TypeId::of::<$name>(),
+                    }]
+                }
+            }
+        )*
+    };
+}
+
+export_struct_abi!(AuctionView, CollectionInfo);
+
 // Marketplace Events
 sol! {
-    event AuctionCreated(uint256 indexed auctionId, address indexed nftContract, uint256 indexed tokenId, uint256 reservePrice, uint256 endTime);
-    event BidPlaced(uint256 indexed auctionId, address indexed bidder, uint256 amount);
-    event AuctionSettled(uint256 indexed auctionId, address indexed winner, uint256 amount);
+    event MarketplaceInitialized(address indexed platformOwner, uint256 platformFeePercentage);
+    event AuctionCreated(uint256 indexed auctionId, address indexed nftContract, uint256 indexed tokenId, address seller, uint256 reservePrice, uint256 duration, uint256 endTime, uint256 platformFeePercentage);
+    event BidPlaced(uint256 indexed auctionId, address indexed bidder, address indexed previousBidder, uint256 amount, uint256 refund);
+    event AuctionSettled(uint256 indexed auctionId, address indexed winner, address indexed seller, uint256 amount, uint256 sellerProceeds, uint256 platformFee, address nftRecipient);
     event AuctionCanceled(uint256 indexed auctionId, address indexed seller);
-    event PlatformFeeUpdated(uint256 newFeePercentage);
-    event FundsWithdrawn(address indexed user, uint256 amount);
+    event PlatformFeeUpdated(uint256 previousFeePercentage, uint256 newFeePercentage);
+    event PlatformFeeChangeQueued(uint256 newFeePercentage, uint256 executeAfter);
+    event PlatformFeeChangeExecuted(uint256 previousFeePercentage, uint256 newFeePercentage);
+    event PlatformFeeChangeCanceled(uint256 newFeePercentage);
+    event FundsWithdrawn(address indexed user, address indexed recipient, uint256 amount, uint256 remainingBalance);
+    event Paused(address indexed account);
+    event Unpaused(address indexed account);
+    event AuctionEmergencyCanceled(uint256 indexed auctionId, address indexed seller, address indexed bidder, uint256 refundAmount);
+    event AuctionForceCanceled(uint256 indexed auctionId, address indexed seller, address indexed bidder, uint256 refundAmount, uint8 reason);
+    event Erc1155AuctionCreated(uint256 indexed auctionId, address indexed nftContract, uint256 indexed tokenId, address seller, uint256 amount, uint256 reservePrice, uint256 duration, uint256 endTime, uint256 platformFeePercentage);
+    event BundleAuctionCreated(uint256 indexed auctionId, address indexed seller, address[] nftContracts, uint256[] tokenIds, uint256 reservePrice, uint256 duration, uint256 endTime, uint256 platformFeePercentage);
+    event OrderFulfilled(bytes32 indexed orderHash, address indexed seller, address indexed buyer, address nftContract, uint256 tokenId, uint256 price, uint256 sellerProceeds, uint256 platformFee);
+    event OrderCanceled(address indexed seller, uint256 nonce);
+    event MinOrderNonceUpdated(address indexed seller, uint256 minNonce);
+    event ListingCreated(uint256 indexed listingId, address indexed seller, address indexed nftContract, uint256 tokenId, uint256 price);
+    event Erc1155ListingCreated(uint256 indexed listingId, address indexed seller, address indexed nftContract, uint256 tokenId, uint256 amount, uint256 price);
+    event ListingCanceled(uint256 indexed listingId, address indexed seller);
+    event ListingSold(uint256 indexed listingId, address indexed buyer, address indexed seller, uint256 price, uint256 sellerProceeds, uint256 platformFee);
+    event NftTransferFailed(uint256 indexed auctionId, address indexed recipient);
+    event NftClaimed(uint256 indexed auctionId, address indexed recipient, uint256 sellerProceeds, uint256 platformFee);
+    event WinningBidRefunded(uint256 indexed auctionId, address indexed bidder, uint256 amount);
+    event OutbidRefundSent(uint256 indexed auctionId, address indexed bidder, uint256 amount);
+    event OutbidRefundCredited(uint256 indexed auctionId, address indexed bidder, uint256 amount);
+    event PlatformOwnershipTransferStarted(address indexed previousOwner, address indexed newOwner);
+    event PlatformOwnershipTransferred(address indexed previousOwner, address indexed newOwner);
+    event FeeRecipientUpdated(address indexed previousRecipient, address indexed newRecipient);
+    event CurationModeUpdated(bool enabled);
+    event CollectionAdded(address indexed nftContract);
+    event CollectionRemoved(address indexed nftContract);
+    event CollectionFeeUpdated(address indexed nftContract, bool hasOverride, uint256 feePercentage);
+    event SellerFeeUpdated(address indexed seller, bool hasOverride, uint256 feePercentage);
+    event CollectionFeeChangeQueued(address indexed nftContract, bool hasOverride, uint256 feePercentage, uint256 executeAfter);
+    event SellerFeeChangeQueued(address indexed seller, bool hasOverride, uint256 feePercentage, uint256 executeAfter);
+    event FeePayeesUpdated(address[] accounts, uint256[] sharesBps);
+    event ReferralShareUpdated(uint256 previousShareBps, uint256 newShareBps);
+    event AuctionCreatorShareSet(uint256 indexed auctionId, address indexed creator, uint256 shareBps);
+    event ReferralPaid(address indexed referrer, address indexed nftContract, uint256 indexed tokenId, uint256 amount);
+    event CreatorFeePaid(uint256 indexed auctionId, address indexed creator, uint256 amount);
+    event ProxyBidRaised(uint256 indexed auctionId, address indexed bidder, address indexed challenger, uint256 amount, uint256 challengerRefund);
+    event ProxyBidIncreased(uint256 indexed auctionId, address indexed bidder);
+    event ProxyEscrowRefunded(uint256 indexed auctionId, address indexed bidder, uint256 amount);
+    event ReserveCommitted(uint256 indexed auctionId, bytes32 commitment);
+    event ReserveRevealed(uint256 indexed auctionId, uint256 reservePrice, bool reserveMet);
+    event AuctionGateSet(uint256 indexed auctionId, uint8 gateKind, bytes32 merkleRoot, address collection);
+    event BidderApproved(uint256 indexed auctionId, address indexed bidder);
+    event BidSignerUpdated(address indexed previousSigner, address indexed newSigner);
+    event AccountBlocklistUpdated(address indexed account, bool blocked);
+    event TokenBlocklistUpdated(address indexed nftContract, uint256 indexed tokenId, bool blocked);
+    event DurationBoundsUpdated(uint256 minDuration, uint256 maxDuration);
+    event MaxFeePercentageUpdated(uint256 previousMaxFeePercentage, uint256 newMaxFeePercentage);
+    event MinBidIncrementUpdated(uint256 previousIncrementBps, uint256 newIncrementBps);
+    event ExtensionWindowUpdated(uint256 previousWindow, uint256 newWindow);
+    event AuctionExtended(uint256 indexed auctionId, uint256 newEndTime);
 }
 
 // Error definitions
 sol! {
+    #![sol(all_derives)]
+
     error AlreadyInitialized();
     error AuctionNotFound();
     error AuctionNotActive();
@@ -67,9 +227,48 @@ sol! {
     error InvalidFeePercentage();
     error ERC721InvalidTokenId();
     error NotApprovedForTransfer();
+    error ContractPaused();
+    error ContractNotPaused();
+    error NotPendingPlatformOwner();
+    error InvalidFeeRecipient();
+    error NoPendingFeeChange();
+    error FeeChangeTimelocked();
+    error NothingToClaim();
+    error NotAuctionWinner();
+    error RefundNotAvailable();
+    error InvalidBundleSize();
+    error InvalidAmount();
+    error InvalidListingData();
+    error InvalidSignature();
+    error OrderExpired();
+    error OrderNonceInvalid();
+    error UnsupportedCurrency();
+    error IncorrectPayment();
+    error ListingNotFound();
+    error ListingNotActive();
+    error NotListingSeller();
+    error InvalidPrice();
+    error Reentrancy();
+    error CollectionNotAllowed();
+    error InvalidFeePayees();
+    error InvalidCreatorShare();
+    error InvalidReferralShare();
+    error ReserveNotRevealed();
+    error InvalidReserveReveal();
+    error InvalidGate();
+    error BidderNotAllowed();
+    error AccountBlocked();
+    error TokenBlocked();
+    error InvalidBidIncrement();
+    error InvalidExtensionWindow();
+    error PageTooLarge();
+    error InvalidRecipient();
+    error InvalidWithdrawalAmount();
+    error SellerCannotBid();
+    error InvalidDeposit();
 }
 
-#[derive(SolidityError)]
+#[derive(SolidityError, Debug)]
 pub enum MarketplaceError {
     AlreadyInitialized(AlreadyInitialized),
     AuctionNotFound(AuctionNotFound),
@@ -88,6 +287,53 @@ pub enum MarketplaceError {
     InvalidFeePercentage(InvalidFeePercentage),
     ERC721InvalidTokenId(ERC721InvalidTokenId),
     NotApprovedForTransfer(NotApprovedForTransfer),
+    ContractPaused(ContractPaused),
+    ContractNotPaused(ContractNotPaused),
+    NotPendingPlatformOwner(NotPendingPlatformOwner),
+    InvalidFeeRecipient(InvalidFeeRecipient),
+    NoPendingFeeChange(NoPendingFeeChange),
+    FeeChangeTimelocked(FeeChangeTimelocked),
+    NothingToClaim(NothingToClaim),
+    NotAuctionWinner(NotAuctionWinner),
+    RefundNotAvailable(RefundNotAvailable),
+    InvalidBundleSize(InvalidBundleSize),
+    InvalidAmount(InvalidAmount),
+    InvalidListingData(InvalidListingData),
+    InvalidSignature(InvalidSignature),
+    OrderExpired(OrderExpired),
+    OrderNonceInvalid(OrderNonceInvalid),
+    UnsupportedCurrency(UnsupportedCurrency),
+    IncorrectPayment(IncorrectPayment),
+    ListingNotFound(ListingNotFound),
+    ListingNotActive(ListingNotActive),
+    NotListingSeller(NotListingSeller),
+    InvalidPrice(InvalidPrice),
+    Reentrancy(Reentrancy),
+    CollectionNotAllowed(CollectionNotAllowed),
+    InvalidFeePayees(InvalidFeePayees),
+    InvalidCreatorShare(InvalidCreatorShare),
+    InvalidReferralShare(InvalidReferralShare),
+    ReserveNotRevealed(ReserveNotRevealed),
+    InvalidReserveReveal(InvalidReserveReveal),
+    InvalidGate(InvalidGate),
+    BidderNotAllowed(BidderNotAllowed),
+    AccountBlocked(AccountBlocked),
+    TokenBlocked(TokenBlocked),
+    InvalidBidIncrement(InvalidBidIncrement),
+    InvalidExtensionWindow(InvalidExtensionWindow),
+    PageTooLarge(PageTooLarge),
+    InvalidRecipient(InvalidRecipient),
+    InvalidWithdrawalAmount(InvalidWithdrawalAmount),
+    SellerCannotBid(SellerCannotBid),
+    InvalidDeposit(InvalidDeposit),
+}
+
+// Bundle lot item
+sol_storage! {
+    pub struct BundleItem {
+        address nft_contract;     // NFT contract address
+        uint256 token_id;        // NFT token ID
+    }
 }
 
 // Auction structure
@@ -101,6 +347,84 @@ sol_storage! {
         address current_bidder;  // Current highest bidder
         uint256 end_time;        // Auction end timestamp
         bool settled;            // Whether auction is settled
+        uint256 platform_fee_percentage; // Fee snapshot taken at creation
+        address nft_claimant;    // Owed the NFT after a failed settlement transfer
+        uint256 refund_available_at; // When the winner may take a refund instead
+        bool proceeds_pending;   // Sale proceeds held until the NFT is delivered
+        bool is_bundle;          // Lot of several NFTs sold together
+        BundleItem[] bundle_items; // Bundle contents (empty for single auctions)
+        bool is_erc1155;         // Token is an ERC1155 edition rather than an ERC721
+        uint256 amount;          // ERC1155 units sold (0 for ERC721)
+        bool cancelled;          // Canceled rather than sold (also marked settled)
+        address referrer;        // Referrer of the current high bid
+        address creator;         // Paid creator_share_bps of the winning bid
+        uint256 creator_share_bps; // Creator's cut, taken from seller proceeds
+        uint256 max_bid;         // High bidder's escrowed ceiling (>= current_bid for proxy bids)
+        bytes32 reserve_commitment; // keccak256(abi.encode(reserve, salt)) of a hidden reserve
+        bool reserve_revealed;   // Hidden reserve has been revealed
+        uint256 hidden_reserve;  // Revealed hidden reserve (reserve_price is then the starting price)
+        uint8 gate_kind;         // Who may bid: GATE_NONE, GATE_MERKLE, GATE_SIGNATURE or GATE_HOLDER
+        bytes32 gate_merkle_root; // Root of allowed bidder addresses (GATE_MERKLE)
+        address gate_collection; // Bidders must hold a token from this ERC721 (GATE_HOLDER)
+        mapping(uint256 => mapping(address => bool)) approved_bidders; // Passed the Merkle or signature gate, per gate_epoch
+        uint256 referral_share_bps; // Referral share snapshot taken at creation
+        uint256 gate_epoch;      // Bumped on every gate change so earlier approvals lapse
+    }
+}
+
+// Non-custodial fixed-price listing (NFT stays with the seller until sold)
+sol_storage! {
+    pub struct Listing {
+        address nft_contract;     // NFT contract address
+        uint256 token_id;        // NFT token ID
+        address seller;          // NFT seller
+        uint256 price;           // Fixed sale price
+        bool active;             // Open for purchase
+        bool is_erc1155;         // Sells ERC1155 units rather than an ERC721
+        uint256 amount;          // ERC1155 units sold (0 for ERC721)
+    }
+}
+
+// Curation settings for an NFT collection
+sol_storage! {
+    pub struct Collection {
+        bool allowed;            // On the curated allowlist
+        bool has_fee_override;   // Charge fee_percentage instead of the platform fee
+        uint256 fee_percentage;  // Per-collection fee (basis points)
+        bool pending_has_override; // Queued override change that raises the fee
+        uint256 pending_fee_percentage;
+        uint256 pending_fee_eta; // When the queued change takes effect, 0 if none
+    }
+}
+
+// Treasury account receiving a share of platform fees
+sol_storage! {
+    #[derive(Erase)]
+    pub struct FeePayee {
+        address account;         // Credited in user_balances
+        uint256 share_bps;       // Share of each platform fee (basis points of 10000)
+    }
+}
+
+// Fee tier for an individual seller
+sol_storage! {
+    pub struct SellerFee {
+        bool has_fee_override;   // Charge fee_percentage instead of the collection or platform fee
+        uint256 fee_percentage;  // Per-seller fee (basis points)
+        bool pending_has_override; // Queued override change that raises the fee
+        uint256 pending_fee_percentage;
+        uint256 pending_fee_eta; // When the queued change takes effect, 0 if none
+    }
+}
+
+// Owner-tunable auction and fee parameters
+sol_storage! {
+    pub struct MarketplaceConfig {
+        uint256 min_duration;           // Shortest allowed auction (seconds)
+        uint256 max_duration;           // Longest allowed auction (seconds)
+        uint256 max_fee_percentage;     // Ceiling for platform, collection and seller fees (basis points)
+        uint256 min_bid_increment_bps;  // Outbids must beat the current bid by this much
+        uint256 extension_window;       // Bids this close to the end push it back (seconds), 0 disables
     }
 }
 
@@ -119,32 +443,121 @@ sol_storage! {
         uint256 platform_fee_percentage;                // 5% = 500 (basis points)
         address platform_owner;
         mapping(address => uint256) user_balances;      // withdrawable balances
+
+        // Emergency stop
+        bool paused;                                    // blocks new auctions, bids and settlement
+
+        // Ownership handover and fee routing
+        address pending_platform_owner;                 // set by transfer, cleared on accept
+        address fee_recipient;                          // receives platform fees at settlement
+
+        // Timelocked fee increases
+        uint256 pending_platform_fee;                   // queued fee (basis points)
+        uint256 pending_platform_fee_eta;               // earliest execution time, 0 if none queued
+
+        // Signed order nonces
+        mapping(address => mapping(uint256 => bool)) order_nonce_used; // filled or canceled
+        mapping(address => uint256) min_order_nonce;    // orders below this nonce are invalid
+
+        // Fixed-price listings
+        uint256 next_listing_id;
+        mapping(uint256 => Listing) listings;           // listingId => Listing
+
+        // Reentrancy guard
+        bool locked;                                    // held for the duration of every mutating call
+
+        // Collection curation
+        bool curation_enabled;                          // only allowlisted collections may trade
+        mapping(address => Collection) collections;     // nftContract => curation settings
+
+        // Seller fee tiers
+        mapping(address => SellerFee) seller_fees;      // seller => fee override
+
+        // Platform revenue split
+        FeePayee[] fee_payees;                          // empty: all fees go to fee_recipient
+
+        // Referral rewards
+        uint256 referral_share_bps;                     // referrer's cut of the platform fee
+
+        // Gated auctions
+        address bid_signer;                             // signs BidAuthorization for GATE_SIGNATURE auctions
+
+        // Blocklists for sanctioned accounts and reported-stolen tokens
+        mapping(address => bool) blocked_accounts;
+        mapping(address => mapping(uint256 => bool)) blocked_tokens; // nftContract => tokenId => blocked
+
+        // Marketplace parameters, set to the defaults on initialize
+        MarketplaceConfig config;
+
+        // ERC1155 units held for auctions, used to verify receiver hook deposits
+        mapping(address => mapping(uint256 => uint256)) erc1155_escrow; // nftContract => id => units
     }
 }
 
 
 const ONE_DAY: u64 = 86400; // 24 hours in seconds
+const FEE_CHANGE_DELAY: u64 = 2 * ONE_DAY; // Minimum wait before a fee increase applies
+const NFT_CLAIM_TIMEOUT: u64 = 7 * ONE_DAY; // Wait before a winner can refund an undeliverable NFT
+const MAX_BUNDLE_SIZE: usize = 50; // Upper bound on items per bundle to keep settlement within gas
+const MAX_BATCH_AUCTIONS: usize = 50; // Upper bound on auctions created in one create_auctions call
+const MAX_FEE_PAYEES: usize = 10; // Upper bound on revenue split recipients
+const TOTAL_SHARE_BPS: u64 = 10000; // Payee shares must add up to 100%
+const MAX_CREATOR_SHARE_BPS: u64 = 1000; // Creators take at most 10% of a sale
+const MAX_REFERRAL_SHARE_BPS: u64 = 5000; // Referrers take at most half of the platform fee
+const NO_SHARE: (Address, U256) = (Address::ZERO, U256::ZERO); // No referrer or creator cut
+const RESERVE_REVEAL_WINDOW: u64 = 2 * ONE_DAY; // After this, an unrevealed hidden reserve counts as not met
+const MAX_EXTENSION_WINDOW: u64 = ONE_DAY; // Upper bound on the anti-sniping extension
+const MAX_DURATION_CAP: u64 = 12 * DEFAULT_MAX_DURATION; // Upper bound on the configurable max duration
+
+// Parameter defaults applied on initialize
+const DEFAULT_MIN_DURATION: u64 = 1; // Any non-zero duration
+const DEFAULT_MAX_DURATION: u64 = 30 * ONE_DAY;
+const DEFAULT_MAX_FEE_PERCENTAGE: u64 = 1000; // 10%
+const DEFAULT_MIN_BID_INCREMENT_BPS: u64 = 500; // 5%
+const DEFAULT_EXTENSION_WINDOW: u64 = 0; // No anti-sniping extension
+
+// Bidder gates for holder-only and partner auctions
+const GATE_NONE: u8 = 0; // Anyone may bid
+const GATE_MERKLE: u8 = 1; // Bidder proves membership in a Merkle tree of addresses
+const GATE_SIGNATURE: u8 = 2; // Bidder presents a BidAuthorization signed by the platform bid signer
+const GATE_HOLDER: u8 = 3; // Bidder holds at least one token of an ERC721 collection
+
+// AuctionView status values
+const STATUS_ACTIVE: u8 = 0; // Open for bids
+const STATUS_ENDED: u8 = 1; // Past its end time, waiting for settlement
+const STATUS_SETTLED: u8 = 2; // Sold, or closed without a sale
+const STATUS_CANCELLED: u8 = 3; // Canceled by the seller or platform owner
+const MAX_AUCTION_PAGE_SIZE: usize = 100; // Upper bound on auctions returned by one batched view
+const REFUND_GAS_LIMIT: u64 = 10_000; // Gas for pushing a refund to an outbid bidder; enough for wallets, too little to grief
+
+// CollectionInfo sources
+const INFO_NONE: u8 = 0; // No contract code, or no collection interface answered; name and symbol are placeholders
+const INFO_MULTI_COLLECTION: u8 = 1; // Per-collection details from IMultiCollectionNFT
+const INFO_ERC721_METADATA: u8 = 2; // Not a multi-collection contract; name and symbol from ERC721 metadata
+const INFO_MULTI_COLLECTION_REVERTED: u8 = 3; // Declares IMultiCollectionNFT via ERC-165 but the lookup failed (e.g. unknown token); name and symbol from ERC721 metadata if available
+
+// ERC1155 receiver hook return values and ERC165 interface ids
+const ERC1155_RECEIVED: [u8; 4] = [0xf2, 0x3a, 0x6e, 0x61]; // onERC1155Received selector
+const ERC1155_BATCH_RECEIVED: [u8; 4] = [0xbc, 0x19, 0x7c, 0x81]; // onERC1155BatchReceived selector
+const ERC165_INTERFACE_ID: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+const ERC1155_RECEIVER_INTERFACE_ID: [u8; 4] = [0x4e, 0x23, 0x12, 0xe0];
+
+// EIP-712 signed orders
+const ECRECOVER: Address = address!("0000000000000000000000000000000000000001");
+const EIP712_DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const ORDER_TYPE: &str = "Order(address seller,address nftContract,uint256 tokenId,uint256 price,address currency,uint256 expiry,uint256 nonce)";
+const BID_AUTHORIZATION_TYPE: &str = "BidAuthorization(uint256 auctionId,address bidder)";
+const DOMAIN_NAME: &str = "NeonMarketplace";
+const DOMAIN_VERSION: &str = "1";
+// secp256k1 curve order / 2, upper bound for `s` to reject malleable signatures
+const SECP256K1_HALF_ORDER: U256 = U256::from_limbs([0xdfe92f46681b20a0, 0x5d576e7357a4501d, 0xffffffffffffffff, 0x7fffffffffffffff]);
 
 #[public]
 impl NeonMarketplace {
 
     /// Initialize the marketplace contract
     pub fn initialize(&mut self, platform_fee_percentage: U256) -> Result<(), MarketplaceError> {
-        if self.initialized.get() {
-            return Err(MarketplaceError::AlreadyInitialized(AlreadyInitialized{}));
-        }
-
-        // Validate fee percentage (max 10% = 1000 basis points)
-        if platform_fee_percentage > U256::from(1000) {
-            return Err(MarketplaceError::InvalidFeePercentage(InvalidFeePercentage{}));
-        }
-
-        self.initialized.set(true);
-        self.next_auction_id.set(U256::from(1));
-        self.platform_fee_percentage.set(platform_fee_percentage);
-        self.platform_owner.set(self.vm().msg_sender());
-
-        Ok(())
+        self.non_reentrant(|this| this.initialize_inner(platform_fee_percentage))
     }
 
     /// Create auction for existing NFT (from any ERC721 contract)
@@ -155,285 +568,389 @@ impl NeonMarketplace {
         reserve_price: U256,
         duration: U256,
     ) -> Result<U256, MarketplaceError> {
-        // Validate inputs
-        if reserve_price == U256::ZERO {
-            return Err(MarketplaceError::InvalidReservePrice(InvalidReservePrice{}));
-        }
-
-        if duration == U256::ZERO || duration > U256::from(30 * ONE_DAY) {
-            return Err(MarketplaceError::InvalidDuration(InvalidDuration{}));
-        }
+        self.non_reentrant(|this| this.create_erc721_auction(nft_contract, token_id, reserve_price, duration, Address::ZERO, U256::ZERO))
+    }
 
-        // Check if caller owns the NFT using static call
-        let nft = IERC721::new(nft_contract);
-        let owner = nft.owner_of(Call::new(), token_id).map_err(|_| MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))?;
+    /// Create one auction per token of `nft_contract` in a single call. The
+    /// marketplace must be approved for all of the caller's tokens and token
+    /// ids must be distinct. Every entry is checked before any auction opens
+    /// and the call reverts if any is invalid; returns the new auction ids.
+    pub fn create_auctions(
+        &mut self,
+        nft_contract: Address,
+        token_ids: Vec<U256>,
+        reserve_prices: Vec<U256>,
+        durations: Vec<U256>,
+    ) -> Result<Vec<U256>, MarketplaceError> {
+        self.non_reentrant(|this| this.create_auctions_inner(nft_contract, token_ids, reserve_prices, durations))
+    }
 
-        if owner != self.vm().msg_sender() {
-            return Err(MarketplaceError::NotTokenOwner(NotTokenOwner{}));
-        }
+    /// Create auction for an ERC721 NFT that pays `creator` a share of the
+    /// winning bid, in basis points, out of the seller's proceeds
+    pub fn create_auction_with_creator(
+        &mut self,
+        nft_contract: Address,
+        token_id: U256,
+        reserve_price: U256,
+        duration: U256,
+        creator: Address,
+        creator_share_bps: U256,
+    ) -> Result<U256, MarketplaceError> {
+        self.non_reentrant(|this| this.create_erc721_auction(nft_contract, token_id, reserve_price, duration, creator, creator_share_bps))
+    }
 
-        // Check if marketplace is approved to transfer this NFT
-        let approved = nft.get_approved(Call::new(), token_id).map_err(|_| MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))?;
-        let is_approved_for_all = nft.is_approved_for_all(Call::new(), self.vm().msg_sender(), self.vm().contract_address()).map_err(|_| MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))?;
+    /// Create auction for an ERC721 NFT with a public starting price and a
+    /// hidden reserve committed as `keccak256(abi.encode(reserve, salt))`.
+    /// The seller reveals the reserve with `reveal_reserve` once bidding ends.
+    /// A seller who never reveals can't force a sale: after the reveal window
+    /// anyone may settle, the reserve counts as not met, the NFT goes back to
+    /// the seller and the high bidder is refunded in full.
+    pub fn create_auction_with_hidden_reserve(
+        &mut self,
+        nft_contract: Address,
+        token_id: U256,
+        starting_price: U256,
+        duration: U256,
+        reserve_commitment: B256,
+    ) -> Result<U256, MarketplaceError> {
+        self.non_reentrant(|this| this.create_auction_with_hidden_reserve_inner(nft_contract, token_id, starting_price, duration, reserve_commitment))
+    }
 
-        if approved != self.vm().contract_address() && !is_approved_for_all {
-            return Err(MarketplaceError::NotApprovedForTransfer(NotApprovedForTransfer{}));
-        }
+    /// Reveal a hidden reserve after bidding ends (only seller). A leading
+    /// proxy bid whose ceiling covers the reserve is raised to meet it.
+    pub fn reveal_reserve(&mut self, auction_id: U256, reserve_price: U256, salt: B256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.reveal_reserve_inner(auction_id, reserve_price, salt))
+    }
 
-        let auction_id = self.next_auction_id.get();
-        let end_time = U256::from(self.vm().block_timestamp()) + duration;
-        let sender = self.vm().msg_sender();
+    /// Create a single auction selling several NFTs as one lot.
+    /// Every `(nft_contract, token_id)` item is escrowed in this call.
+    pub fn create_bundle_auction(
+        &mut self,
+        items: Vec<(Address, U256)>,
+        reserve_price: U256,
+        duration: U256,
+    ) -> Result<U256, MarketplaceError> {
+        self.non_reentrant(|this| this.create_bundle_auction_inner(items, reserve_price, duration))
+    }
 
-        // Create auction
-        let mut auction = self.auctions.setter(auction_id);
-        auction.nft_contract.set(nft_contract);
-        auction.token_id.set(token_id);
-        auction.seller.set(sender);
-        auction.reserve_price.set(reserve_price);
-        auction.current_bid.set(U256::ZERO);
-        auction.current_bidder.set(Address::ZERO);
-        auction.end_time.set(end_time);
-        auction.settled.set(false);
+    /// Cancel an auction (only if no bids placed)
+    pub fn cancel_auction(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.cancel_auction_inner(auction_id))
+    }
 
-        // Transfer NFT to contract
-        nft.transfer_from(Call::new(), self.vm().msg_sender(), self.vm().contract_address(), token_id).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+    /// Place a bid on an auction
+    #[payable]
+    pub fn place_bid(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.bid(auction_id, Address::ZERO, false))
+    }
 
-        // Increment auction ID
-        self.next_auction_id.set(auction_id + U256::from(1));
+    /// Place a bid on an auction crediting `referrer` with a share of the
+    /// platform fee if this bid wins
+    #[payable]
+    pub fn place_bid_with_referrer(&mut self, auction_id: U256, referrer: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.bid(auction_id, referrer, false))
+    }
 
-        // Emit event
-        evm::log(AuctionCreated {
-            auctionId: auction_id,
-            nftContract: nft_contract,
-            tokenId: token_id,
-            reservePrice: reserve_price,
-            endTime: end_time,
-        });
+    /// Escrow `msg_value` as a maximum bid. The marketplace bids only as much
+    /// as needed to lead and raises it by the minimum increment when
+    /// challenged; unused escrow is refunded. The current leader calls this
+    /// again to raise their maximum.
+    #[payable]
+    pub fn place_proxy_bid(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.bid(auction_id, Address::ZERO, true))
+    }
 
-        Ok(auction_id)
+    /// Place a proxy bid crediting `referrer` with a share of the platform fee
+    /// if it wins
+    #[payable]
+    pub fn place_proxy_bid_with_referrer(&mut self, auction_id: U256, referrer: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.bid(auction_id, referrer, true))
     }
 
-    /// Cancel an auction (only if no bids placed)
-    pub fn cancel_auction(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
-        let auction = self.auctions.getter(auction_id);
+    /// Restrict who may bid on an auction (only seller, before any bids).
+    /// `gate_kind` 0 opens bidding to everyone, 1 checks `merkle_root`,
+    /// 2 requires a platform-signed authorization, 3 requires holding a
+    /// token of the ERC721 `collection`. Bidders approved under the previous
+    /// gate must authorize again.
+    pub fn set_auction_gate(&mut self, auction_id: U256, gate_kind: u8, merkle_root: B256, collection: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_auction_gate_inner(auction_id, gate_kind, merkle_root, collection))
+    }
 
-        // Check if auction exists
-        if auction.seller.get() == Address::ZERO {
-            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
-        }
+    /// Create auction for an ERC721 NFT that is gated from the start, so no
+    /// bid can land before the gate is in place. Takes the same gate
+    /// arguments as `set_auction_gate`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_gated_auction(
+        &mut self,
+        nft_contract: Address,
+        token_id: U256,
+        reserve_price: U256,
+        duration: U256,
+        gate_kind: u8,
+        merkle_root: B256,
+        collection: Address,
+    ) -> Result<U256, MarketplaceError> {
+        self.non_reentrant(|this| this.create_gated_auction_inner(nft_contract, token_id, reserve_price, duration, gate_kind, merkle_root, collection))
+    }
 
-        // Check if caller is the seller
-        if auction.seller.get() != self.vm().msg_sender() {
-            return Err(MarketplaceError::NotAuctionSeller(NotAuctionSeller{}));
-        }
+    /// Prove eligibility for a Merkle or signature gated auction, after
+    /// which any bid function may be used. `proof` is the Merkle proof for
+    /// the caller's address; `signature` is the bid signer's authorization.
+    pub fn authorize_bidder(&mut self, auction_id: U256, proof: Vec<B256>, signature: Bytes) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.approve_bidder(auction_id, &proof, &signature))
+    }
 
-        // Check if auction is already settled
-        if auction.settled.get() {
-            return Err(MarketplaceError::AuctionAlreadySettled(AuctionAlreadySettled{}));
-        }
+    /// Prove eligibility for a gated auction and bid `msg_value` in one call
+    #[payable]
+    pub fn place_gated_bid(&mut self, auction_id: U256, proof: Vec<B256>, signature: Bytes) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.place_gated_bid_inner(auction_id, proof, signature))
+    }
 
-        // Check if auction has bids (can only cancel if no bids)
-        if auction.current_bidder.get() != Address::ZERO {
-            return Err(MarketplaceError::AuctionHasBids(AuctionHasBids{}));
-        }
+    /// Settle an auction after it ends (can be called by anyone)
+    pub fn settle_auction(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.settle_auction_inner(auction_id))
+    }
 
-        let nft_contract = auction.nft_contract.get();
-        let token_id = auction.token_id.get();
-        let seller = auction.seller.get();
-        let contract_addr = self.vm().contract_address();
+    /// List an NFT at a fixed price without escrow. The seller keeps the NFT
+    /// and must keep the marketplace approved until it sells.
+    pub fn create_listing(&mut self, nft_contract: Address, token_id: U256, price: U256) -> Result<U256, MarketplaceError> {
+        self.non_reentrant(|this| this.create_listing_inner(nft_contract, token_id, price))
+    }
 
-        // Mark auction as settled (cancelled)
-        self.auctions.setter(auction_id).settled.set(true);
+    /// List `amount` ERC1155 units at a fixed price for the lot, without escrow.
+    /// The seller keeps the units and must keep the marketplace approved for all.
+    pub fn create_erc1155_listing(&mut self, nft_contract: Address, token_id: U256, amount: U256, price: U256) -> Result<U256, MarketplaceError> {
+        self.non_reentrant(|this| this.create_erc1155_listing_inner(nft_contract, token_id, amount, price))
+    }
 
-        // Return NFT to seller
-        let nft = IERC721::new(nft_contract);
-        nft.transfer_from(Call::new(), contract_addr, seller, token_id).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+    /// Cancel a fixed-price listing (only seller)
+    pub fn cancel_listing(&mut self, listing_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.cancel_listing_inner(listing_id))
+    }
 
-        // Emit cancellation event
-        evm::log(AuctionCanceled {
-            auctionId: auction_id,
-            seller: seller,
-        });
+    /// Buy a listed NFT at its fixed price. Ownership and approval are
+    /// re-checked, so stale listings revert.
+    #[payable]
+    pub fn buy(&mut self, listing_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.buy_listing(listing_id, Address::ZERO))
+    }
 
-        Ok(())
+    /// Buy a listed NFT crediting `referrer` with a share of the platform fee
+    #[payable]
+    pub fn buy_with_referrer(&mut self, listing_id: U256, referrer: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.buy_listing(listing_id, referrer))
     }
 
-    /// Place a bid on an auction
+    /// Buy an NFT with a seller's off-chain signed EIP-712 order.
+    /// The NFT moves straight from the seller's wallet; payment is split into
+    /// seller proceeds and platform fee in withdrawable balances. Orders must be
+    /// priced in native ETH (`currency == address(0)`); other currencies revert.
     #[payable]
-    pub fn place_bid(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
-        let auction = self.auctions.getter(auction_id);
+    pub fn fulfill_order(&mut self, order: Order, signature: Bytes) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.fulfill_order_inner(order, signature))
+    }
 
-        // Check if auction exists
-        if auction.seller.get() == Address::ZERO {
-            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
-        }
+    /// Cancel a single signed order by its nonce
+    pub fn cancel_order(&mut self, nonce: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.cancel_order_inner(nonce))
+    }
 
-        // Check if auction is active
-        if U256::from(self.vm().block_timestamp()) >= auction.end_time.get() || auction.settled.get() {
-            return Err(MarketplaceError::AuctionNotActive(AuctionNotActive{}));
-        }
+    /// Invalidate every signed order with a nonce below `min_nonce`
+    pub fn cancel_all_orders(&mut self, min_nonce: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.cancel_all_orders_inner(min_nonce))
+    }
 
-        let bid_amount = self.vm().msg_value();
-        let current_bid = auction.current_bid.get();
-        let reserve_price = auction.reserve_price.get();
-        let sender = self.vm().msg_sender();
+    /// Retry delivering an NFT whose transfer failed at settlement (can be called by anyone).
+    /// Held sale proceeds are released once the winner receives the token.
+    pub fn claim_nft(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.claim_nft_inner(auction_id))
+    }
 
-        // Check if bid meets minimum requirements
-        let min_bid = if current_bid == U256::ZERO {
-            reserve_price
-        } else {
-            current_bid + (current_bid / U256::from(20)) // 5% increment
-        };
+    /// Refund the winning bid if the NFT still cannot be delivered after
+    /// `NFT_CLAIM_TIMEOUT` (only auction winner). Delivery is retried first and
+    /// completes the sale if it succeeds; otherwise the token becomes claimable
+    /// by the seller. A winner contract whose own ERC1155 receiver hook rejects
+    /// the lot is not refunded.
+    pub fn refund_undeliverable_nft(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.refund_undeliverable_nft_inner(auction_id))
+    }
 
-        if bid_amount < min_bid {
-            return Err(MarketplaceError::BidTooLow(BidTooLow{}));
-        }
+    /// Update platform fee percentage (only platform owner).
+    /// Decreases apply immediately; increases are queued for `FEE_CHANGE_DELAY`
+    /// and applied with `execute_platform_fee_change`.
+    pub fn update_platform_fee(&mut self, new_fee_percentage: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.update_platform_fee_inner(new_fee_percentage))
+    }
 
-        // Refund previous bidder
-        let previous_bidder = auction.current_bidder.get();
-        if previous_bidder != Address::ZERO {
-            let previous_balance = self.user_balances.getter(previous_bidder).get();
-            self.user_balances.setter(previous_bidder).set(previous_balance + current_bid);
-        }
+    /// Apply a queued fee increase once its delay has passed (only platform owner)
+    pub fn execute_platform_fee_change(&mut self) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.execute_platform_fee_change_inner())
+    }
 
-        // Update auction with new bid
-        let mut auction_mut = self.auctions.setter(auction_id);
-        auction_mut.current_bid.set(bid_amount);
-        auction_mut.current_bidder.set(sender);
+    /// Drop a queued fee increase (only platform owner)
+    pub fn cancel_platform_fee_change(&mut self) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.cancel_platform_fee_change_inner())
+    }
 
-        // Emit event
-        evm::log(BidPlaced {
-            auctionId: auction_id,
-            bidder: sender,
-            amount: bid_amount,
-        });
+    /// Start handing platform ownership to a new address (only platform owner).
+    /// The new owner must call `accept_platform_ownership`; passing the zero
+    /// address cancels a pending transfer.
+    pub fn transfer_platform_ownership(&mut self, new_owner: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.transfer_platform_ownership_inner(new_owner))
+    }
 
-        Ok(())
+    /// Accept a pending platform ownership transfer (only pending owner)
+    pub fn accept_platform_ownership(&mut self) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.accept_platform_ownership_inner())
     }
 
-    /// Settle an auction after it ends (can be called by anyone)
-    pub fn settle_auction(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
-        // Get auction details in one read
-        let auction = self.auctions.getter(auction_id);
-        let nft_contract = auction.nft_contract.get();
-        let token_id = auction.token_id.get();
-        let seller = auction.seller.get();
-        let reserve_price = auction.reserve_price.get();
-        let current_bid = auction.current_bid.get();
-        let current_bidder = auction.current_bidder.get();
-        let end_time = auction.end_time.get();
-        let settled = auction.settled.get();
+    /// Update the address credited with platform fees (only platform owner)
+    pub fn update_fee_recipient(&mut self, new_recipient: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.update_fee_recipient_inner(new_recipient))
+    }
 
-        // Check if auction exists
-        if seller == Address::ZERO {
-            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
-        }
+    /// Split platform fees among treasury accounts. Shares are basis points
+    /// and must sum to 10000; an empty list sends all fees to the fee
+    /// recipient again (only platform owner)
+    pub fn set_fee_payees(&mut self, payees: Vec<(Address, U256)>) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_fee_payees_inner(payees))
+    }
 
-        // Check if auction has ended
-        if U256::from(self.vm().block_timestamp()) < end_time {
-            return Err(MarketplaceError::AuctionNotEnded(AuctionNotEnded{}));
-        }
+    /// Block or unblock an account from trading and withdrawing (only
+    /// platform owner). Funds it already has escrowed stay frozen while blocked.
+    pub fn set_account_blocked(&mut self, account: Address, blocked: bool) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_account_blocked_inner(account, blocked))
+    }
 
-        // Check if already settled
-        if settled {
-            return Err(MarketplaceError::AuctionAlreadySettled(AuctionAlreadySettled{}));
-        }
+    /// Set the shortest and longest allowed auction duration in seconds, the
+    /// longest at most 360 days. Existing auctions keep their end time (only
+    /// platform owner)
+    pub fn set_duration_bounds(&mut self, min_duration: U256, max_duration: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_duration_bounds_inner(min_duration, max_duration))
+    }
 
-        // Mark as settled
-        self.auctions.setter(auction_id).settled.set(true);
+    /// Set the ceiling for platform, collection and seller fees in basis
+    /// points, at most 9000 so a sale can always pay the largest creator
+    /// share. It cannot drop below the current or queued platform fee;
+    /// overrides above a lowered ceiling are charged at the ceiling
+    /// (only platform owner)
+    pub fn set_max_fee_percentage(&mut self, max_fee_percentage: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_max_fee_percentage_inner(max_fee_percentage))
+    }
 
-        if current_bidder != Address::ZERO && current_bid >= reserve_price {
-            // Calculate platform fee using stored percentage
-            let platform_fee = (current_bid * self.platform_fee_percentage.get()) / U256::from(10000);
-            let seller_amount = current_bid - platform_fee;
+    /// Set how much an outbid must exceed the current bid, in basis points
+    /// (only platform owner)
+    pub fn set_min_bid_increment(&mut self, increment_bps: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_min_bid_increment_inner(increment_bps))
+    }
 
-            // Transfer NFT to winner
-            let nft = IERC721::new(nft_contract);
-            nft.transfer_from(Call::new(), self.vm().contract_address(), current_bidder, token_id).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+    /// Set the anti-sniping window: a bid placed this many seconds or less
+    /// before the end pushes the end back to `now + window`. Zero disables
+    /// extensions (only platform owner)
+    pub fn set_extension_window(&mut self, window: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_extension_window_inner(window))
+    }
 
-            // Add seller proceeds to withdrawable balance
-            let seller_balance = self.user_balances.getter(seller).get();
-            self.user_balances.setter(seller).set(seller_balance + seller_amount);
+    /// Block or unblock a token from being listed, bought or settled (only
+    /// platform owner). An escrowed token stays in the marketplace while blocked.
+    pub fn set_token_blocked(&mut self, nft_contract: Address, token_id: U256, blocked: bool) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_token_blocked_inner(nft_contract, token_id, blocked))
+    }
 
-            // Add platform fee to platform owner balance
-            let platform_owner = self.platform_owner.get();
-            let platform_balance = self.user_balances.getter(platform_owner).get();
-            self.user_balances.setter(platform_owner).set(platform_balance + platform_fee);
+    /// Set the platform key that signs bidder authorizations for signature
+    /// gated auctions (only platform owner)
+    pub fn set_bid_signer(&mut self, signer: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_bid_signer_inner(signer))
+    }
 
-            // Emit settlement event
-            evm::log(AuctionSettled {
-                auctionId: auction_id,
-                winner: current_bidder,
-                amount: current_bid,
-            });
-        } else {
-            // No valid bids - return NFT to seller
-            let nft = IERC721::new(nft_contract);
-            nft.transfer_from(Call::new(), self.vm().contract_address(), seller, token_id).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+    /// Set the share of the platform fee, in basis points, paid to the
+    /// referrer of a sale (max 5000; only platform owner)
+    pub fn set_referral_share(&mut self, share_bps: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_referral_share_inner(share_bps))
+    }
 
-            // Emit settlement event with no winner
-            evm::log(AuctionSettled {
-                auctionId: auction_id,
-                winner: Address::ZERO,
-                amount: U256::ZERO,
-            });
-        }
+    /// Turn curation mode on or off. While on, only allowlisted collections
+    /// can be auctioned, listed or bought (only platform owner)
+    pub fn set_curation_enabled(&mut self, enabled: bool) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_curation_enabled_inner(enabled))
+    }
 
-        Ok(())
+    /// Add a collection to the curated allowlist (only platform owner)
+    pub fn add_collection(&mut self, nft_contract: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.add_collection_inner(nft_contract))
     }
 
-    /// Update platform fee percentage (only platform owner)
-    pub fn update_platform_fee(&mut self, new_fee_percentage: U256) -> Result<(), MarketplaceError> {
-        // Check if caller is platform owner
-        if self.vm().msg_sender() != self.platform_owner.get() {
-            return Err(MarketplaceError::NotPlatformOwner(NotPlatformOwner{}));
-        }
+    /// Remove a collection from the allowlist, dropping any fee override
+    /// (only platform owner)
+    pub fn remove_collection(&mut self, nft_contract: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.remove_collection_inner(nft_contract))
+    }
 
-        // Validate fee percentage (max 10% = 1000 basis points)
-        if new_fee_percentage > U256::from(1000) {
-            return Err(MarketplaceError::InvalidFeePercentage(InvalidFeePercentage{}));
-        }
+    /// Charge an allowlisted collection its own fee instead of the platform
+    /// fee on new sales (only platform owner). Changes that lower the fee apply
+    /// immediately; increases take effect after `FEE_CHANGE_DELAY`.
+    pub fn set_collection_fee(&mut self, nft_contract: Address, fee_percentage: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_collection_fee_inner(nft_contract, fee_percentage))
+    }
 
-        self.platform_fee_percentage.set(new_fee_percentage);
+    /// Return a collection to the platform fee (only platform owner). Delayed
+    /// like `set_collection_fee` if the platform fee is higher.
+    pub fn clear_collection_fee(&mut self, nft_contract: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.clear_collection_fee_inner(nft_contract))
+    }
 
-        // Emit event
-        evm::log(PlatformFeeUpdated {
-            newFeePercentage: new_fee_percentage,
-        });
+    /// Give a seller their own fee tier, which takes priority over collection
+    /// fees and the platform fee on new sales (only platform owner). Changes
+    /// that lower the fee apply immediately; increases take effect after `FEE_CHANGE_DELAY`.
+    pub fn set_seller_fee(&mut self, seller: Address, fee_percentage: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.set_seller_fee_inner(seller, fee_percentage))
+    }
 
-        Ok(())
+    /// Remove a seller's fee tier (only platform owner). Delayed like
+    /// `set_seller_fee` if the platform fee is higher.
+    pub fn clear_seller_fee(&mut self, seller: Address) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.clear_seller_fee_inner(seller))
     }
 
-    /// Withdraw accumulated funds
-    pub fn withdraw(&mut self) -> Result<(), MarketplaceError> {
-        let sender = self.vm().msg_sender();
-        let balance = self.user_balances.getter(sender).get();
+    /// Pause auction creation, bidding and settlement (only platform owner)
+    pub fn pause(&mut self) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.pause_inner())
+    }
 
-        if balance == U256::ZERO {
-            return Err(MarketplaceError::InsufficientBalance(InsufficientBalance{}));
-        }
+    /// Resume normal marketplace operation (only platform owner)
+    pub fn unpause(&mut self) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.unpause_inner())
+    }
 
-        // Reset balance before transfer (reentrancy protection)
-        self.user_balances.setter(sender).set(U256::ZERO);
+    /// Force-cancel an auction while paused, returning the NFT to the seller
+    /// and crediting any high bid back to the bidder. If the NFT contract
+    /// refuses the transfer, the seller can `claim_nft` it later
+    /// (only platform owner)
+    pub fn emergency_cancel_auction(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.emergency_cancel_auction_inner(auction_id))
+    }
 
-        // Transfer funds using vm().transfer_eth
-        match self.vm().transfer_eth(sender, balance) {
-            Ok(_) => {
-                evm::log(FundsWithdrawn {
-                    user: sender,
-                    amount: balance,
-                });
-                Ok(())
-            }
-            Err(_) => {
-                // Restore balance on failed transfer
-                self.user_balances.setter(sender).set(balance);
-                Err(MarketplaceError::TransferFailed(TransferFailed{}))
-            }
-        }
+    /// Cancel a fraudulent or mistaken auction even after bids, returning the
+    /// NFT to the seller (or leaving it for the seller to `claim_nft` if the
+    /// NFT contract refuses) and crediting the high bid back to the bidder.
+    /// `reason` is an off-chain reason code recorded in the event (only platform owner)
+    pub fn force_cancel_auction(&mut self, auction_id: U256, reason: u8) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.force_cancel_auction_inner(auction_id, reason))
+    }
+
+    /// Withdraw accumulated funds
+    pub fn withdraw(&mut self) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.withdraw_inner())
+    }
+
+    /// Send `amount` of the caller's balance to `recipient`, for wallets that
+    /// can't receive ETH themselves or to leave part of the balance in place
+    pub fn withdraw_to(&mut self, recipient: Address, amount: U256) -> Result<(), MarketplaceError> {
+        self.non_reentrant(|this| this.withdraw_to_inner(recipient, amount))
     }
 
     /// Get auction details
+    #[allow(clippy::type_complexity)]
     pub fn get_auction(&self, auction_id: U256) -> Result<(Address, U256, Address, U256, U256, Address, U256, bool), MarketplaceError> {
         let auction = self.auctions.getter(auction_id);
 
@@ -453,6 +970,189 @@ impl NeonMarketplace {
         ))
     }
 
+    /// Get several auctions by id, in the order given
+    pub fn get_auctions(&self, auction_ids: Vec<U256>) -> Result<Vec<AuctionView>, MarketplaceError> {
+        if auction_ids.len() > MAX_AUCTION_PAGE_SIZE {
+            return Err(MarketplaceError::PageTooLarge(PageTooLarge{}));
+        }
+
+        auction_ids.into_iter().map(|auction_id| self.auction_view(auction_id)).collect()
+    }
+
+    /// Get up to `count` consecutive auctions starting at `start`. The page
+    /// stops early at the last auction created
+    pub fn get_auctions_range(&self, start: U256, count: U256) -> Result<Vec<AuctionView>, MarketplaceError> {
+        if count > U256::from(MAX_AUCTION_PAGE_SIZE) {
+            return Err(MarketplaceError::PageTooLarge(PageTooLarge{}));
+        }
+
+        // Auction ids start at 1
+        let start = start.max(U256::from(1));
+        let end = start.saturating_add(count).min(self.next_auction_id.get());
+
+        let mut auctions = Vec::new();
+        let mut auction_id = start;
+        while auction_id < end {
+            auctions.push(self.auction_view(auction_id)?);
+            auction_id += U256::from(1);
+        }
+
+        Ok(auctions)
+    }
+
+    /// Get the platform fee (basis points) locked in when the auction was created
+    pub fn get_auction_platform_fee(&self, auction_id: U256) -> Result<U256, MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok(auction.platform_fee_percentage.get())
+    }
+
+    /// Get listing details: (nft_contract, token_id, seller, price, active)
+    pub fn get_listing(&self, listing_id: U256) -> Result<(Address, U256, Address, U256, bool), MarketplaceError> {
+        let listing = self.listings.getter(listing_id);
+
+        if listing.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::ListingNotFound(ListingNotFound{}));
+        }
+
+        Ok((
+            listing.nft_contract.get(),
+            listing.token_id.get(),
+            listing.seller.get(),
+            listing.price.get(),
+            listing.active.get(),
+        ))
+    }
+
+    /// Check a listing can be bought right now: it is active, the seller still
+    /// owns the NFT (or enough ERC1155 units) and the marketplace is still
+    /// approved. Stale listings return false.
+    pub fn is_listing_valid(&self, listing_id: U256) -> Result<bool, MarketplaceError> {
+        let listing = self.listings.getter(listing_id);
+        let seller = listing.seller.get();
+
+        if seller == Address::ZERO || !listing.active.get() {
+            return Ok(false);
+        }
+
+        Ok(self.check_tradable(listing.nft_contract.get(), listing.token_id.get(), &[seller]).is_ok() && self.check_listing_holdings(listing_id).is_ok())
+    }
+
+    /// Check if a listing sells ERC1155 units
+    pub fn is_erc1155_listing(&self, listing_id: U256) -> Result<bool, MarketplaceError> {
+        let listing = self.listings.getter(listing_id);
+
+        if listing.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::ListingNotFound(ListingNotFound{}));
+        }
+
+        Ok(listing.is_erc1155.get())
+    }
+
+    /// Get number of token units sold in a listing (1 for ERC721)
+    pub fn get_listing_amount(&self, listing_id: U256) -> Result<U256, MarketplaceError> {
+        let listing = self.listings.getter(listing_id);
+
+        if listing.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::ListingNotFound(ListingNotFound{}));
+        }
+
+        if listing.is_erc1155.get() {
+            Ok(listing.amount.get())
+        } else {
+            Ok(U256::from(1))
+        }
+    }
+
+    /// Get next listing ID
+    pub fn get_next_listing_id(&self) -> Result<U256, MarketplaceError> {
+        Ok(self.next_listing_id.get())
+    }
+
+    /// Get the EIP-712 domain separator for signed orders
+    pub fn get_domain_separator(&self) -> Result<B256, MarketplaceError> {
+        Ok(self.domain_separator())
+    }
+
+    /// Get the EIP-712 digest a seller signs for an order
+    pub fn hash_order(&self, order: Order) -> Result<B256, MarketplaceError> {
+        Ok(self.order_digest(&order))
+    }
+
+    /// Check whether an order can still be filled as far as nonces are concerned
+    pub fn is_order_nonce_valid(&self, seller: Address, nonce: U256) -> Result<bool, MarketplaceError> {
+        Ok(nonce >= self.min_order_nonce.get(seller) && !self.order_nonce_used.getter(seller).get(nonce))
+    }
+
+    /// Get the lowest nonce a seller's orders may use
+    pub fn get_min_order_nonce(&self, seller: Address) -> Result<U256, MarketplaceError> {
+        Ok(self.min_order_nonce.get(seller))
+    }
+
+    /// Check if an auction sells ERC1155 units
+    pub fn is_erc1155_auction(&self, auction_id: U256) -> Result<bool, MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok(auction.is_erc1155.get())
+    }
+
+    /// Get number of token units sold in an auction (1 for ERC721)
+    pub fn get_auction_amount(&self, auction_id: U256) -> Result<U256, MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok(self.lot_amount(auction_id))
+    }
+
+    /// Check if an auction sells a bundle of NFTs
+    pub fn is_bundle_auction(&self, auction_id: U256) -> Result<bool, MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok(auction.is_bundle.get())
+    }
+
+    /// Get every `(nft_contract, token_id)` sold in an auction's lot
+    pub fn get_bundle_items(&self, auction_id: U256) -> Result<Vec<(Address, U256)>, MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok(self.lot_items(auction_id))
+    }
+
+    /// Get a pending NFT claim: (claimant, refund available at, proceeds held).
+    /// Claimant is zero when nothing is owed.
+    pub fn get_nft_claim(&self, auction_id: U256) -> Result<(Address, U256, bool), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok((
+            auction.nft_claimant.get(),
+            auction.refund_available_at.get(),
+            auction.proceeds_pending.get(),
+        ))
+    }
+
     /// Check if auction is active
     pub fn is_auction_active(&self, auction_id: U256) -> Result<bool, MarketplaceError> {
         let auction = self.auctions.getter(auction_id);
@@ -465,6 +1165,93 @@ impl NeonMarketplace {
         Ok(is_active)
     }
 
+    /// Check whether the high bid meets the reserve. For hidden reserves this
+    /// is only known after `reveal_reserve`.
+    pub fn reserve_met(&self, auction_id: U256) -> Result<bool, MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        let reserve_price = if auction.reserve_commitment.get() == B256::ZERO {
+            auction.reserve_price.get()
+        } else if auction.reserve_revealed.get() {
+            auction.hidden_reserve.get()
+        } else {
+            return Err(MarketplaceError::ReserveNotRevealed(ReserveNotRevealed{}));
+        };
+
+        Ok(auction.current_bidder.get() != Address::ZERO && auction.current_bid.get() >= reserve_price)
+    }
+
+    /// Compute the commitment for a hidden reserve: `keccak256(abi.encode(reserve, salt))`
+    pub fn hash_reserve(&self, reserve_price: U256, salt: B256) -> Result<B256, MarketplaceError> {
+        Ok(keccak((reserve_price, salt).abi_encode()))
+    }
+
+    /// Check whether an auction was canceled rather than sold
+    pub fn is_auction_cancelled(&self, auction_id: U256) -> Result<bool, MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok(auction.cancelled.get())
+    }
+
+    /// Check whether curation mode is on
+    pub fn is_curation_enabled(&self) -> Result<bool, MarketplaceError> {
+        Ok(self.curation_enabled.get())
+    }
+
+    /// Check whether a collection is on the curated allowlist
+    pub fn is_collection_allowed(&self, nft_contract: Address) -> Result<bool, MarketplaceError> {
+        Ok(self.collections.getter(nft_contract).allowed.get())
+    }
+
+    /// Get a collection's fee override as `(has_override, fee_percentage)`
+    pub fn get_collection_fee(&self, nft_contract: Address) -> Result<(bool, U256), MarketplaceError> {
+        Ok(self.collection_fee_override(nft_contract))
+    }
+
+    /// Get a collection's queued fee increase as `(has_override, fee_percentage, execute_after)`,
+    /// all zero if none is waiting
+    pub fn get_pending_collection_fee(&self, nft_contract: Address) -> Result<(bool, U256, U256), MarketplaceError> {
+        let collection = self.collections.getter(nft_contract);
+        let eta = collection.pending_fee_eta.get();
+
+        if eta == U256::ZERO || U256::from(self.vm().block_timestamp()) >= eta {
+            return Ok((false, U256::ZERO, U256::ZERO));
+        }
+        Ok((collection.pending_has_override.get(), collection.pending_fee_percentage.get(), eta))
+    }
+
+    /// Get a seller's fee tier as `(has_override, fee_percentage)`
+    pub fn get_seller_fee(&self, seller: Address) -> Result<(bool, U256), MarketplaceError> {
+        Ok(self.seller_fee_override(seller))
+    }
+
+    /// Get a seller's queued fee increase as `(has_override, fee_percentage, execute_after)`,
+    /// all zero if none is waiting
+    pub fn get_pending_seller_fee(&self, seller: Address) -> Result<(bool, U256, U256), MarketplaceError> {
+        let seller_fee = self.seller_fees.getter(seller);
+        let eta = seller_fee.pending_fee_eta.get();
+
+        if eta == U256::ZERO || U256::from(self.vm().block_timestamp()) >= eta {
+            return Ok((false, U256::ZERO, U256::ZERO));
+        }
+        Ok((seller_fee.pending_has_override.get(), seller_fee.pending_fee_percentage.get(), eta))
+    }
+
+    /// Fee (basis points) a sale of `nft_contract` by `seller` would pay now:
+    /// seller tier, then collection override, then the platform fee, capped
+    /// at the fee ceiling
+    pub fn effective_fee(&self, nft_contract: Address, seller: Address) -> Result<U256, MarketplaceError> {
+        Ok(self.fee_for(nft_contract, seller))
+    }
+
     /// Get user's withdrawable balance
     pub fn get_balance(&self, user_address: Address) -> Result<U256, MarketplaceError> {
         Ok(self.user_balances.getter(user_address).get())
@@ -480,34 +1267,2693 @@ impl NeonMarketplace {
         Ok(self.platform_fee_percentage.get())
     }
 
-    /// Get platform owner
-    pub fn get_platform_owner(&self) -> Result<Address, MarketplaceError> {
-        Ok(self.platform_owner.get())
+    /// Get queued fee increase and the time it can be executed (zeros if none)
+    pub fn get_pending_platform_fee(&self) -> Result<(U256, U256), MarketplaceError> {
+        Ok((self.pending_platform_fee.get(), self.pending_platform_fee_eta.get()))
     }
 
-    /// Get collection information for a token (if using multi-collection NFT)
-    pub fn get_token_collection_info(&self, nft_contract: Address, token_id: U256) -> Result<(U256, String, String, Address), MarketplaceError> {
-        // Try to call the multi-collection NFT interface
-        let multi_nft = IMultiCollectionNFT::new(nft_contract);
+    /// Get pending platform owner (zero if no transfer in progress)
+    pub fn get_pending_platform_owner(&self) -> Result<Address, MarketplaceError> {
+        Ok(self.pending_platform_owner.get())
+    }
 
-        match multi_nft.token_collection(Call::new(), token_id) {
-            Ok(collection_id) => {
-                match multi_nft.get_collection(Call::new(), collection_id) {
-                    Ok((name, symbol, creator, _base_uri, _next_token_id)) => {
-                        Ok((collection_id, name, symbol, creator))
-                    }
-                    Err(_) => Err(MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))
-                }
-            }
-            Err(_) => {
-                // Not a multi-collection NFT, return default values
-                Ok((U256::ZERO, "Unknown Collection".to_string(), "UNK".to_string(), Address::ZERO))
-            }
+    /// Get the address credited with platform fees
+    pub fn get_fee_recipient(&self) -> Result<Address, MarketplaceError> {
+        Ok(self.current_fee_recipient())
+    }
+
+    /// Get an auction's bidder gate as `(gate_kind, merkle_root, collection)`
+    pub fn get_auction_gate(&self, auction_id: U256) -> Result<(u8, B256, Address), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
         }
+
+        Ok((auction.gate_kind.get().to::<u8>(), auction.gate_merkle_root.get(), auction.gate_collection.get()))
     }
 
-    /// Get platform fee percentage
-    pub fn get_platform_fee_percentage(&self) -> Result<U256, MarketplaceError> {
-        Ok(self.platform_fee_percentage.get())
+    /// Check whether `bidder` currently passes an auction's gate
+    pub fn is_bidder_allowed(&self, auction_id: U256, bidder: Address) -> Result<bool, MarketplaceError> {
+        if self.auctions.getter(auction_id).seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok(self.check_bidder_allowed(auction_id, bidder).is_ok())
+    }
+
+    /// Check whether an account is blocklisted
+    pub fn is_account_blocked(&self, account: Address) -> Result<bool, MarketplaceError> {
+        Ok(self.blocked_accounts.get(account))
+    }
+
+    /// Check whether a token is blocklisted
+    pub fn is_token_blocked(&self, nft_contract: Address, token_id: U256) -> Result<bool, MarketplaceError> {
+        Ok(self.blocked_tokens.getter(nft_contract).get(token_id))
     }
-}
\ No newline at end of file
+
+    /// Get the marketplace parameters as `(min_duration, max_duration,
+    /// max_fee_percentage, min_bid_increment_bps, extension_window)`
+    pub fn get_config(&self) -> Result<(U256, U256, U256, U256, U256), MarketplaceError> {
+        Ok((
+            self.config.min_duration.get(),
+            self.config.max_duration.get(),
+            self.config.max_fee_percentage.get(),
+            self.config.min_bid_increment_bps.get(),
+            self.config.extension_window.get(),
+        ))
+    }
+
+    /// Get the platform key that authorizes bidders on signature gated auctions
+    pub fn get_bid_signer(&self) -> Result<Address, MarketplaceError> {
+        Ok(self.bid_signer.get())
+    }
+
+    /// Get the EIP-712 digest the bid signer signs to authorize a bidder
+    pub fn hash_bid_authorization(&self, auction_id: U256, bidder: Address) -> Result<B256, MarketplaceError> {
+        Ok(self.bid_authorization_digest(auction_id, bidder))
+    }
+
+    /// Get the share of the platform fee paid to referrers (basis points)
+    pub fn get_referral_share(&self) -> Result<U256, MarketplaceError> {
+        Ok(self.referral_share_bps.get())
+    }
+
+    /// Get an auction's payout parties as `(referrer, creator, creator_share_bps)`
+    pub fn get_auction_splits(&self, auction_id: U256) -> Result<(Address, Address, U256), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        Ok((auction.referrer.get(), auction.creator.get(), auction.creator_share_bps.get()))
+    }
+
+    /// Get the platform revenue split as `(account, share_bps)` pairs
+    pub fn get_fee_payees(&self) -> Result<Vec<(Address, U256)>, MarketplaceError> {
+        let mut payees = Vec::new();
+        for i in 0..self.fee_payees.len() {
+            if let Some(payee) = self.fee_payees.getter(i) {
+                payees.push((payee.account.get(), payee.share_bps.get()));
+            }
+        }
+        Ok(payees)
+    }
+
+    /// Check if the marketplace is paused
+    pub fn is_paused(&self) -> Result<bool, MarketplaceError> {
+        Ok(self.paused.get())
+    }
+
+    /// Get platform owner
+    pub fn get_platform_owner(&self) -> Result<Address, MarketplaceError> {
+        Ok(self.platform_owner.get())
+    }
+
+    /// Get collection information for a token. Multi-collection NFTs report
+    /// their per-collection details; other contracts fall back to ERC721
+    /// `name()`/`symbol()`. `source` says where the data came from (see the
+    /// `INFO_*` constants), and `contractURI` is the ERC-7572 URI if the
+    /// contract has one. Contracts that declare `IMultiCollectionNFT` through
+    /// ERC-165 are always treated as multi-collection, so a failed lookup is
+    /// reported as `INFO_MULTI_COLLECTION_REVERTED`.
+    pub fn get_token_collection_info(&self, nft_contract: Address, token_id: U256) -> Result<CollectionInfo, MarketplaceError> {
+        // Accounts without code have no metadata to read
+        if self.vm().code_size(nft_contract) == 0 {
+            return Ok(CollectionInfo {
+                source: INFO_NONE,
+                collectionId: U256::ZERO,
+                name: "Unknown Collection".to_string(),
+                symbol: "UNK".to_string(),
+                creator: Address::ZERO,
+                contractURI: String::new(),
+            });
+        }
+
+        let contract_uri = self.nft_view(nft_contract, IERC7572::contractURICall {}).map(|uri| uri._0).unwrap_or_default();
+        let declares_multi_collection = self.nft_view(nft_contract, IERC165::supportsInterfaceCall { interfaceId: multi_collection_interface_id() })
+            .map(|supported| supported._0)
+            .unwrap_or(false);
+
+        // Contracts without ERC-165 are still asked, and count as
+        // multi-collection only if the lookup succeeds
+        let lookup = self.nft_view(nft_contract, IMultiCollectionNFT::tokenCollectionCall { tokenId: token_id }).and_then(|collection_id| {
+            let collection_id = collection_id._0;
+            self.nft_view(nft_contract, IMultiCollectionNFT::getCollectionCall { collectionId: collection_id }).map(|collection| (collection_id, collection))
+        });
+
+        let source = match lookup {
+            Ok((collection_id, collection)) => {
+                return Ok(CollectionInfo {
+                    source: INFO_MULTI_COLLECTION,
+                    collectionId: collection_id,
+                    name: collection._0,
+                    symbol: collection._1,
+                    creator: collection._2,
+                    contractURI: contract_uri,
+                });
+            }
+            Err(_) if declares_multi_collection => INFO_MULTI_COLLECTION_REVERTED,
+            Err(_) => INFO_ERC721_METADATA,
+        };
+
+        let name = self.nft_view(nft_contract, IERC721Metadata::nameCall {});
+        let symbol = self.nft_view(nft_contract, IERC721Metadata::symbolCall {});
+        let (source, name, symbol) = match (name, symbol) {
+            (Ok(name), Ok(symbol)) => (source, name._0, symbol._0),
+            _ if source == INFO_MULTI_COLLECTION_REVERTED => (source, String::new(), String::new()),
+            _ => (INFO_NONE, "Unknown Collection".to_string(), "UNK".to_string()),
+        };
+
+        Ok(CollectionInfo {
+            source,
+            collectionId: U256::ZERO,
+            name,
+            symbol,
+            creator: Address::ZERO,
+            contractURI: contract_uri,
+        })
+    }
+
+    /// Get a token's metadata URI from its contract (ERC721 `tokenURI`)
+    pub fn get_token_uri(&self, nft_contract: Address, token_id: U256) -> Result<String, MarketplaceError> {
+        self.nft_view(nft_contract, IERC721Metadata::tokenURICall { tokenId: token_id })
+            .map(|uri| uri._0)
+            .map_err(|_| MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))
+    }
+
+    /// Get platform fee percentage
+    pub fn get_platform_fee_percentage(&self) -> Result<U256, MarketplaceError> {
+        Ok(self.platform_fee_percentage.get())
+    }
+
+    /// ERC1155 receiver hook. Sellers list editions by calling
+    /// `safeTransferFrom(seller, marketplace, id, amount, abi.encode(reservePrice, duration))`
+    /// on the ERC1155 contract; the deposit is escrowed and an auction created for `from`.
+    /// The seller must make the transfer themselves (`operator == from`), and the
+    /// marketplace's balance is checked to cover the deposit.
+    #[selector(name = "onERC1155Received")]
+    pub fn on_erc1155_received(
+        &mut self,
+        operator: Address,
+        from: Address,
+        id: U256,
+        value: U256,
+        data: Bytes,
+    ) -> Result<FixedBytes<4>, MarketplaceError> {
+        self.non_reentrant(|this| this.on_erc1155_received_inner(operator, from, id, value, data))
+    }
+
+    /// ERC1155 batch receiver hook. Creates one auction per token id, all
+    /// sharing the `abi.encode(reservePrice, duration)` terms in `data`.
+    #[selector(name = "onERC1155BatchReceived")]
+    pub fn on_erc1155_batch_received(
+        &mut self,
+        operator: Address,
+        from: Address,
+        ids: Vec<U256>,
+        values: Vec<U256>,
+        data: Bytes,
+    ) -> Result<FixedBytes<4>, MarketplaceError> {
+        self.non_reentrant(|this| this.on_erc1155_batch_received_inner(operator, from, ids, values, data))
+    }
+
+    /// ERC165 interface detection
+    pub fn supports_interface(&self, interface_id: FixedBytes<4>) -> Result<bool, MarketplaceError> {
+        Ok(interface_id == FixedBytes::from(ERC165_INTERFACE_ID) || interface_id == FixedBytes::from(ERC1155_RECEIVER_INTERFACE_ID))
+    }
+}
+
+// Entry point bodies, run by the public methods above while holding the reentrancy lock
+impl NeonMarketplace {
+    fn initialize_inner(&mut self, platform_fee_percentage: U256) -> Result<(), MarketplaceError> {
+        if self.initialized.get() {
+            return Err(MarketplaceError::AlreadyInitialized(AlreadyInitialized{}));
+        }
+
+        // Validate fee percentage (max 10% = 1000 basis points)
+        if platform_fee_percentage > U256::from(DEFAULT_MAX_FEE_PERCENTAGE) {
+            return Err(MarketplaceError::InvalidFeePercentage(InvalidFeePercentage{}));
+        }
+
+        let config = &mut self.config;
+        config.min_duration.set(U256::from(DEFAULT_MIN_DURATION));
+        config.max_duration.set(U256::from(DEFAULT_MAX_DURATION));
+        config.max_fee_percentage.set(U256::from(DEFAULT_MAX_FEE_PERCENTAGE));
+        config.min_bid_increment_bps.set(U256::from(DEFAULT_MIN_BID_INCREMENT_BPS));
+        config.extension_window.set(U256::from(DEFAULT_EXTENSION_WINDOW));
+
+        self.initialized.set(true);
+        self.next_auction_id.set(U256::from(1));
+        self.next_listing_id.set(U256::from(1));
+        self.platform_fee_percentage.set(platform_fee_percentage);
+        self.platform_owner.set(self.vm().msg_sender());
+        self.fee_recipient.set(self.vm().msg_sender());
+
+        log(self.vm(), MarketplaceInitialized {
+            platformOwner: self.vm().msg_sender(),
+            platformFeePercentage: platform_fee_percentage,
+        });
+
+        Ok(())
+    }
+
+    fn create_auctions_inner(
+        &mut self,
+        nft_contract: Address,
+        token_ids: Vec<U256>,
+        reserve_prices: Vec<U256>,
+        durations: Vec<U256>,
+    ) -> Result<Vec<U256>, MarketplaceError> {
+        self.when_not_paused()?;
+
+        if token_ids.is_empty() || token_ids.len() > MAX_BATCH_AUCTIONS || token_ids.len() != reserve_prices.len() || token_ids.len() != durations.len() {
+            return Err(MarketplaceError::InvalidListingData(InvalidListingData{}));
+        }
+
+        // One approval check covers every token in the batch
+        let sender = self.vm().msg_sender();
+        let operator = self.vm().contract_address();
+        let is_approved_for_all = self.nft_view(nft_contract, IERC721::isApprovedForAllCall { owner: sender, operator })
+            .map(|approved| approved._0)
+            .unwrap_or(false);
+        if !is_approved_for_all {
+            return Err(MarketplaceError::NotApprovedForTransfer(NotApprovedForTransfer{}));
+        }
+
+        // Validate every entry before opening any auction
+        for (i, (&token_id, (&reserve_price, &duration))) in token_ids.iter().zip(reserve_prices.iter().zip(&durations)).enumerate() {
+            if token_ids[..i].contains(&token_id) {
+                return Err(MarketplaceError::InvalidListingData(InvalidListingData{}));
+            }
+
+            self.validate_auction_terms(reserve_price, duration)?;
+            self.check_tradable(nft_contract, token_id, &[sender])?;
+
+            let owner = self.nft_view(nft_contract, IERC721::ownerOfCall { tokenId: token_id }).map_err(|_| MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))?._0;
+            if owner != sender {
+                return Err(MarketplaceError::NotTokenOwner(NotTokenOwner{}));
+            }
+        }
+
+        let mut auction_ids = Vec::with_capacity(token_ids.len());
+        for ((token_id, reserve_price), duration) in token_ids.into_iter().zip(reserve_prices).zip(durations) {
+            auction_ids.push(self.open_erc721_auction(nft_contract, token_id, reserve_price, duration, Address::ZERO, U256::ZERO)?);
+        }
+
+        Ok(auction_ids)
+    }
+
+    fn create_auction_with_hidden_reserve_inner(
+        &mut self,
+        nft_contract: Address,
+        token_id: U256,
+        starting_price: U256,
+        duration: U256,
+        reserve_commitment: B256,
+    ) -> Result<U256, MarketplaceError> {
+        if reserve_commitment == B256::ZERO {
+            return Err(MarketplaceError::InvalidReservePrice(InvalidReservePrice{}));
+        }
+
+        let auction_id = self.create_erc721_auction(nft_contract, token_id, starting_price, duration, Address::ZERO, U256::ZERO)?;
+        self.auctions.setter(auction_id).reserve_commitment.set(reserve_commitment);
+
+        log(self.vm(), ReserveCommitted {
+            auctionId: auction_id,
+            commitment: reserve_commitment,
+        });
+
+        Ok(auction_id)
+    }
+
+    fn reveal_reserve_inner(&mut self, auction_id: U256, reserve_price: U256, salt: B256) -> Result<(), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+        let seller = auction.seller.get();
+
+        // Check if auction exists
+        if seller == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        if self.vm().msg_sender() != seller {
+            return Err(MarketplaceError::NotAuctionSeller(NotAuctionSeller{}));
+        }
+
+        if auction.settled.get() {
+            return Err(MarketplaceError::AuctionAlreadySettled(AuctionAlreadySettled{}));
+        }
+
+        if U256::from(self.vm().block_timestamp()) < auction.end_time.get() {
+            return Err(MarketplaceError::AuctionNotEnded(AuctionNotEnded{}));
+        }
+
+        let commitment = auction.reserve_commitment.get();
+        if commitment == B256::ZERO || auction.reserve_revealed.get() || keccak((reserve_price, salt).abi_encode()) != commitment {
+            return Err(MarketplaceError::InvalidReserveReveal(InvalidReserveReveal{}));
+        }
+
+        let current_bidder = auction.current_bidder.get();
+        let mut current_bid = auction.current_bid.get();
+        let max_bid = auction.max_bid.get();
+
+        let mut auction_mut = self.auctions.setter(auction_id);
+        auction_mut.reserve_revealed.set(true);
+        auction_mut.hidden_reserve.set(reserve_price);
+        if current_bid < reserve_price && max_bid >= reserve_price {
+            current_bid = reserve_price;
+            auction_mut.current_bid.set(current_bid);
+        }
+
+        log(self.vm(), ReserveRevealed {
+            auctionId: auction_id,
+            reservePrice: reserve_price,
+            reserveMet: current_bidder != Address::ZERO && current_bid >= reserve_price,
+        });
+
+        Ok(())
+    }
+
+    fn create_bundle_auction_inner(
+        &mut self,
+        items: Vec<(Address, U256)>,
+        reserve_price: U256,
+        duration: U256,
+    ) -> Result<U256, MarketplaceError> {
+        self.when_not_paused()?;
+
+        // Validate inputs
+        if items.is_empty() || items.len() > MAX_BUNDLE_SIZE {
+            return Err(MarketplaceError::InvalidBundleSize(InvalidBundleSize{}));
+        }
+
+        self.validate_auction_terms(reserve_price, duration)?;
+
+        for &(nft_contract, token_id) in &items {
+            self.check_tradable(nft_contract, token_id, &[self.vm().msg_sender()])?;
+            self.check_owner_and_approval(nft_contract, token_id, self.vm().msg_sender())?;
+        }
+
+        let auction_id = self.next_auction_id.get();
+        let end_time = U256::from(self.vm().block_timestamp()) + duration;
+        let sender = self.vm().msg_sender();
+        let (first_contract, first_token_id) = items[0];
+
+        // A single-collection bundle takes that collection's fee; mixed lots
+        // skip the collection tier
+        let fee_percentage = if items.iter().all(|&(nft_contract, _)| nft_contract == first_contract) {
+            self.fee_for(first_contract, sender)
+        } else {
+            self.seller_fee(sender).unwrap_or(self.platform_fee_percentage.get()).min(self.config.max_fee_percentage.get())
+        };
+
+        // Create auction; the first item doubles as the headline NFT
+        let mut auction = self.auctions.setter(auction_id);
+        auction.nft_contract.set(first_contract);
+        auction.token_id.set(first_token_id);
+        auction.seller.set(sender);
+        auction.reserve_price.set(reserve_price);
+        auction.current_bid.set(U256::ZERO);
+        auction.current_bidder.set(Address::ZERO);
+        auction.end_time.set(end_time);
+        auction.settled.set(false);
+        auction.platform_fee_percentage.set(fee_percentage);
+        auction.referral_share_bps.set(self.referral_share_bps.get());
+        auction.is_bundle.set(true);
+        for &(nft_contract, token_id) in &items {
+            let mut item = auction.bundle_items.grow();
+            item.nft_contract.set(nft_contract);
+            item.token_id.set(token_id);
+        }
+
+        // Transfer every NFT to contract
+        let contract_addr = self.vm().contract_address();
+        for &(nft_contract, token_id) in &items {
+            self.nft_call(nft_contract, IERC721::transferFromCall { from: sender, to: contract_addr, tokenId: token_id }).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+        }
+
+        // Increment auction ID
+        self.next_auction_id.set(auction_id + U256::from(1));
+
+        log(self.vm(), BundleAuctionCreated {
+            auctionId: auction_id,
+            seller: sender,
+            nftContracts: items.iter().map(|&(nft_contract, _)| nft_contract).collect(),
+            tokenIds: items.iter().map(|&(_, token_id)| token_id).collect(),
+            reservePrice: reserve_price,
+            duration,
+            endTime: end_time,
+            platformFeePercentage: fee_percentage,
+        });
+
+        Ok(auction_id)
+    }
+
+    fn cancel_auction_inner(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        // Check if auction exists
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        // Check if caller is the seller
+        if auction.seller.get() != self.vm().msg_sender() {
+            return Err(MarketplaceError::NotAuctionSeller(NotAuctionSeller{}));
+        }
+
+        // Check if auction is already settled
+        if auction.settled.get() {
+            return Err(MarketplaceError::AuctionAlreadySettled(AuctionAlreadySettled{}));
+        }
+
+        // Check if auction has bids (can only cancel if no bids)
+        if auction.current_bidder.get() != Address::ZERO {
+            return Err(MarketplaceError::AuctionHasBids(AuctionHasBids{}));
+        }
+
+        let seller = auction.seller.get();
+
+        // A blocklisted seller or token can't take the escrow back
+        self.check_lot_tradable(auction_id, &[seller])?;
+
+        // Mark auction as settled (cancelled)
+        let mut auction = self.auctions.setter(auction_id);
+        auction.settled.set(true);
+        auction.cancelled.set(true);
+
+        // Return NFT(s) to seller
+        self.transfer_lot(auction_id, seller)?;
+
+        // Emit cancellation event
+        log(self.vm(), AuctionCanceled {
+            auctionId: auction_id,
+            seller,
+        });
+
+        Ok(())
+    }
+
+    fn set_auction_gate_inner(&mut self, auction_id: U256, gate_kind: u8, merkle_root: B256, collection: Address) -> Result<(), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        // Check if auction exists
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        if auction.seller.get() != self.vm().msg_sender() {
+            return Err(MarketplaceError::NotAuctionSeller(NotAuctionSeller{}));
+        }
+
+        if auction.settled.get() {
+            return Err(MarketplaceError::AuctionAlreadySettled(AuctionAlreadySettled{}));
+        }
+
+        // Gating can't change who is eligible once bidding has started
+        if auction.current_bidder.get() != Address::ZERO {
+            return Err(MarketplaceError::AuctionHasBids(AuctionHasBids{}));
+        }
+
+        self.apply_auction_gate(auction_id, gate_kind, merkle_root, collection)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_gated_auction_inner(
+        &mut self,
+        nft_contract: Address,
+        token_id: U256,
+        reserve_price: U256,
+        duration: U256,
+        gate_kind: u8,
+        merkle_root: B256,
+        collection: Address,
+    ) -> Result<U256, MarketplaceError> {
+        let auction_id = self.create_erc721_auction(nft_contract, token_id, reserve_price, duration, Address::ZERO, U256::ZERO)?;
+        self.apply_auction_gate(auction_id, gate_kind, merkle_root, collection)?;
+        Ok(auction_id)
+    }
+
+    fn place_gated_bid_inner(&mut self, auction_id: U256, proof: Vec<B256>, signature: Bytes) -> Result<(), MarketplaceError> {
+        self.approve_bidder(auction_id, &proof, &signature)?;
+        self.bid(auction_id, Address::ZERO, false)
+    }
+
+    fn settle_auction_inner(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.when_not_paused()?;
+
+        // Get auction details in one read
+        let auction = self.auctions.getter(auction_id);
+        let seller = auction.seller.get();
+        let reserve_price = auction.reserve_price.get();
+        let current_bid = auction.current_bid.get();
+        let current_bidder = auction.current_bidder.get();
+        let end_time = auction.end_time.get();
+        let settled = auction.settled.get();
+        let is_bundle = auction.is_bundle.get();
+        let escrowed_bid = auction.max_bid.get().max(current_bid);
+        let has_hidden_reserve = auction.reserve_commitment.get() != B256::ZERO;
+        let reserve_revealed = auction.reserve_revealed.get();
+        let hidden_reserve = auction.hidden_reserve.get();
+
+        // Check if auction exists
+        if seller == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        // Check if auction has ended
+        if U256::from(self.vm().block_timestamp()) < end_time {
+            return Err(MarketplaceError::AuctionNotEnded(AuctionNotEnded{}));
+        }
+
+        // Check if already settled
+        if settled {
+            return Err(MarketplaceError::AuctionAlreadySettled(AuctionAlreadySettled{}));
+        }
+
+        // A hidden reserve must be revealed first; once the reveal window
+        // lapses the auction settles as if the reserve was not met
+        let reserve_price = if !has_hidden_reserve {
+            reserve_price
+        } else if reserve_revealed {
+            hidden_reserve
+        } else if U256::from(self.vm().block_timestamp()) < end_time + U256::from(RESERVE_REVEAL_WINDOW) {
+            return Err(MarketplaceError::ReserveNotRevealed(ReserveNotRevealed{}));
+        } else {
+            U256::MAX
+        };
+
+        // Escrow involving a blocklisted party or token stays frozen until
+        // the platform owner unblocks it; force-cancelling refunds the bidder
+        // but keeps a blocked lot in escrow
+        self.check_lot_tradable(auction_id, &[seller, current_bidder])?;
+
+        // Mark as settled
+        self.auctions.setter(auction_id).settled.set(true);
+
+        if current_bidder != Address::ZERO && current_bid >= reserve_price {
+            // Return the unused part of a winning proxy bid
+            self.refund_proxy_surplus(auction_id);
+
+            // Bundles move atomically: every item reaches the winner or settlement reverts.
+            // For single NFTs, if the NFT contract refuses, hold the proceeds and let
+            // the winner claim the token later
+            let (seller_proceeds, platform_fee, nft_recipient) = if is_bundle {
+                self.transfer_lot(auction_id, current_bidder)?;
+                let (seller_proceeds, platform_fee) = self.credit_sale_proceeds(auction_id);
+                (seller_proceeds, platform_fee, current_bidder)
+            } else if self.transfer_lot(auction_id, current_bidder).is_ok() {
+                let (seller_proceeds, platform_fee) = self.credit_sale_proceeds(auction_id);
+                (seller_proceeds, platform_fee, current_bidder)
+            } else {
+                let refund_available_at = U256::from(self.vm().block_timestamp() + NFT_CLAIM_TIMEOUT);
+                let mut auction_mut = self.auctions.setter(auction_id);
+                auction_mut.nft_claimant.set(current_bidder);
+                auction_mut.refund_available_at.set(refund_available_at);
+                auction_mut.proceeds_pending.set(true);
+
+                log(self.vm(), NftTransferFailed {
+                    auctionId: auction_id,
+                    recipient: current_bidder,
+                });
+
+                // Proceeds are credited when the NFT is claimed
+                (U256::ZERO, U256::ZERO, Address::ZERO)
+            };
+
+            // Emit settlement event
+            log(self.vm(), AuctionSettled {
+                auctionId: auction_id,
+                winner: current_bidder,
+                seller,
+                amount: current_bid,
+                sellerProceeds: seller_proceeds,
+                platformFee: platform_fee,
+                nftRecipient: nft_recipient,
+            });
+        } else {
+            // A high bid below the reserve goes back to the bidder
+            if current_bidder != Address::ZERO {
+                let bidder_balance = self.user_balances.getter(current_bidder).get();
+                self.user_balances.setter(current_bidder).set(bidder_balance + escrowed_bid);
+
+                log(self.vm(), WinningBidRefunded {
+                    auctionId: auction_id,
+                    bidder: current_bidder,
+                    amount: escrowed_bid,
+                });
+            }
+
+            // No valid bids - return NFT(s) to seller, or leave a single NFT claimable
+            let nft_recipient = if is_bundle {
+                self.transfer_lot(auction_id, seller)?;
+                seller
+            } else if self.transfer_lot(auction_id, seller).is_ok() {
+                seller
+            } else {
+                self.auctions.setter(auction_id).nft_claimant.set(seller);
+
+                log(self.vm(), NftTransferFailed {
+                    auctionId: auction_id,
+                    recipient: seller,
+                });
+
+                Address::ZERO
+            };
+
+            // Emit settlement event with no winner
+            log(self.vm(), AuctionSettled {
+                auctionId: auction_id,
+                winner: Address::ZERO,
+                seller,
+                amount: U256::ZERO,
+                sellerProceeds: U256::ZERO,
+                platformFee: U256::ZERO,
+                nftRecipient: nft_recipient,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn create_listing_inner(&mut self, nft_contract: Address, token_id: U256, price: U256) -> Result<U256, MarketplaceError> {
+        self.when_not_paused()?;
+
+        if price == U256::ZERO {
+            return Err(MarketplaceError::InvalidPrice(InvalidPrice{}));
+        }
+
+        let sender = self.vm().msg_sender();
+        self.check_tradable(nft_contract, token_id, &[sender])?;
+        self.check_owner_and_approval(nft_contract, token_id, sender)?;
+
+        let listing_id = self.next_listing_id.get();
+
+        let mut listing = self.listings.setter(listing_id);
+        listing.nft_contract.set(nft_contract);
+        listing.token_id.set(token_id);
+        listing.seller.set(sender);
+        listing.price.set(price);
+        listing.active.set(true);
+
+        self.next_listing_id.set(listing_id + U256::from(1));
+
+        log(self.vm(), ListingCreated {
+            listingId: listing_id,
+            seller: sender,
+            nftContract: nft_contract,
+            tokenId: token_id,
+            price,
+        });
+
+        Ok(listing_id)
+    }
+
+    fn create_erc1155_listing_inner(&mut self, nft_contract: Address, token_id: U256, amount: U256, price: U256) -> Result<U256, MarketplaceError> {
+        self.when_not_paused()?;
+
+        if price == U256::ZERO {
+            return Err(MarketplaceError::InvalidPrice(InvalidPrice{}));
+        }
+
+        if amount == U256::ZERO {
+            return Err(MarketplaceError::InvalidAmount(InvalidAmount{}));
+        }
+
+        let sender = self.vm().msg_sender();
+        self.check_tradable(nft_contract, token_id, &[sender])?;
+        self.check_erc1155_balance_and_approval(nft_contract, token_id, sender, amount)?;
+
+        let listing_id = self.next_listing_id.get();
+
+        let mut listing = self.listings.setter(listing_id);
+        listing.nft_contract.set(nft_contract);
+        listing.token_id.set(token_id);
+        listing.seller.set(sender);
+        listing.price.set(price);
+        listing.active.set(true);
+        listing.is_erc1155.set(true);
+        listing.amount.set(amount);
+
+        self.next_listing_id.set(listing_id + U256::from(1));
+
+        log(self.vm(), Erc1155ListingCreated {
+            listingId: listing_id,
+            seller: sender,
+            nftContract: nft_contract,
+            tokenId: token_id,
+            amount,
+            price,
+        });
+
+        Ok(listing_id)
+    }
+
+    fn cancel_listing_inner(&mut self, listing_id: U256) -> Result<(), MarketplaceError> {
+        let listing = self.listings.getter(listing_id);
+        let seller = listing.seller.get();
+
+        if seller == Address::ZERO {
+            return Err(MarketplaceError::ListingNotFound(ListingNotFound{}));
+        }
+
+        if seller != self.vm().msg_sender() {
+            return Err(MarketplaceError::NotListingSeller(NotListingSeller{}));
+        }
+
+        if !listing.active.get() {
+            return Err(MarketplaceError::ListingNotActive(ListingNotActive{}));
+        }
+
+        self.listings.setter(listing_id).active.set(false);
+
+        log(self.vm(), ListingCanceled {
+            listingId: listing_id,
+            seller,
+        });
+
+        Ok(())
+    }
+
+    fn fulfill_order_inner(&mut self, order: Order, signature: Bytes) -> Result<(), MarketplaceError> {
+        self.when_not_paused()?;
+
+        // Only native ETH payment is supported
+        if order.currency != Address::ZERO {
+            return Err(MarketplaceError::UnsupportedCurrency(UnsupportedCurrency{}));
+        }
+
+        if self.vm().msg_value() != order.price {
+            return Err(MarketplaceError::IncorrectPayment(IncorrectPayment{}));
+        }
+
+        if U256::from(self.vm().block_timestamp()) >= order.expiry {
+            return Err(MarketplaceError::OrderExpired(OrderExpired{}));
+        }
+
+        if order.nonce < self.min_order_nonce.get(order.seller) || self.order_nonce_used.getter(order.seller).get(order.nonce) {
+            return Err(MarketplaceError::OrderNonceInvalid(OrderNonceInvalid{}));
+        }
+
+        let order_hash = self.order_digest(&order);
+        if self.recover_signer(order_hash, &signature)? != order.seller {
+            return Err(MarketplaceError::InvalidSignature(InvalidSignature{}));
+        }
+
+        // Collection, token and both parties must still be tradable, and the
+        // seller must still own the NFT and have approved the marketplace
+        self.check_tradable(order.nftContract, order.tokenId, &[order.seller, self.vm().msg_sender()])?;
+        self.check_owner_and_approval(order.nftContract, order.tokenId, order.seller)?;
+
+        // Consume the nonce before the external call
+        self.order_nonce_used.setter(order.seller).insert(order.nonce, true);
+
+        let buyer = self.vm().msg_sender();
+        self.nft_call(order.nftContract, IERC721::transferFromCall { from: order.seller, to: buyer, tokenId: order.tokenId }).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+
+        let fee_percentage = self.fee_for(order.nftContract, order.seller);
+        let (seller_proceeds, platform_fee, _, _) = self.credit_sale(order.seller, order.price, fee_percentage, NO_SHARE, NO_SHARE);
+
+        log(self.vm(), OrderFulfilled {
+            orderHash: order_hash,
+            seller: order.seller,
+            buyer,
+            nftContract: order.nftContract,
+            tokenId: order.tokenId,
+            price: order.price,
+            sellerProceeds: seller_proceeds,
+            platformFee: platform_fee,
+        });
+
+        Ok(())
+    }
+
+    fn cancel_order_inner(&mut self, nonce: U256) -> Result<(), MarketplaceError> {
+        let sender = self.vm().msg_sender();
+
+        if self.order_nonce_used.getter(sender).get(nonce) {
+            return Err(MarketplaceError::OrderNonceInvalid(OrderNonceInvalid{}));
+        }
+
+        self.order_nonce_used.setter(sender).insert(nonce, true);
+
+        log(self.vm(), OrderCanceled {
+            seller: sender,
+            nonce,
+        });
+
+        Ok(())
+    }
+
+    fn cancel_all_orders_inner(&mut self, min_nonce: U256) -> Result<(), MarketplaceError> {
+        let sender = self.vm().msg_sender();
+
+        if min_nonce <= self.min_order_nonce.get(sender) {
+            return Err(MarketplaceError::OrderNonceInvalid(OrderNonceInvalid{}));
+        }
+
+        self.min_order_nonce.insert(sender, min_nonce);
+
+        log(self.vm(), MinOrderNonceUpdated {
+            seller: sender,
+            minNonce: min_nonce,
+        });
+
+        Ok(())
+    }
+
+    fn claim_nft_inner(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+        let claimant = auction.nft_claimant.get();
+        let proceeds_pending = auction.proceeds_pending.get();
+
+        // Check if auction exists
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        if claimant == Address::ZERO {
+            return Err(MarketplaceError::NothingToClaim(NothingToClaim{}));
+        }
+
+        self.check_lot_tradable(auction_id, &[claimant])?;
+
+        // Clear the claim before the external call
+        let mut auction_mut = self.auctions.setter(auction_id);
+        auction_mut.nft_claimant.set(Address::ZERO);
+        auction_mut.refund_available_at.set(U256::ZERO);
+        auction_mut.proceeds_pending.set(false);
+
+        self.transfer_lot(auction_id, claimant)?;
+
+        let (seller_proceeds, platform_fee) = if proceeds_pending {
+            self.credit_sale_proceeds(auction_id)
+        } else {
+            (U256::ZERO, U256::ZERO)
+        };
+
+        log(self.vm(), NftClaimed {
+            auctionId: auction_id,
+            recipient: claimant,
+            sellerProceeds: seller_proceeds,
+            platformFee: platform_fee,
+        });
+
+        Ok(())
+    }
+
+    fn refund_undeliverable_nft_inner(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+        let seller = auction.seller.get();
+        let current_bid = auction.current_bid.get();
+        let current_bidder = auction.current_bidder.get();
+
+        // Check if auction exists
+        if seller == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        if self.vm().msg_sender() != current_bidder {
+            return Err(MarketplaceError::NotAuctionWinner(NotAuctionWinner{}));
+        }
+
+        if !auction.proceeds_pending.get() || U256::from(self.vm().block_timestamp()) < auction.refund_available_at.get() {
+            return Err(MarketplaceError::RefundNotAvailable(RefundNotAvailable{}));
+        }
+
+        // Clear the claim before the external call
+        let mut auction_mut = self.auctions.setter(auction_id);
+        auction_mut.nft_claimant.set(Address::ZERO);
+        auction_mut.refund_available_at.set(U256::ZERO);
+        auction_mut.proceeds_pending.set(false);
+
+        // The refund is only for a token that still can't move: try delivering
+        // it once more and complete the sale if that works
+        if self.check_lot_tradable(auction_id, &[current_bidder]).is_ok() && self.transfer_lot(auction_id, current_bidder).is_ok() {
+            let (seller_proceeds, platform_fee) = self.credit_sale_proceeds(auction_id);
+
+            log(self.vm(), NftClaimed {
+                auctionId: auction_id,
+                recipient: current_bidder,
+                sellerProceeds: seller_proceeds,
+                platformFee: platform_fee,
+            });
+
+            return Ok(());
+        }
+
+        // A winner contract that rejects the ERC1155 units itself can't turn
+        // the claim into a free option on the sale
+        if self.rejects_erc1155_lot(auction_id, current_bidder) {
+            return Err(MarketplaceError::RefundNotAvailable(RefundNotAvailable{}));
+        }
+
+        // Hand the claim back to the seller and release the escrowed bid
+        self.auctions.setter(auction_id).nft_claimant.set(seller);
+
+        let bidder_balance = self.user_balances.getter(current_bidder).get();
+        self.user_balances.setter(current_bidder).set(bidder_balance + current_bid);
+
+        log(self.vm(), WinningBidRefunded {
+            auctionId: auction_id,
+            bidder: current_bidder,
+            amount: current_bid,
+        });
+
+        Ok(())
+    }
+
+    fn update_platform_fee_inner(&mut self, new_fee_percentage: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        // Validate fee percentage against the configured ceiling
+        if new_fee_percentage > self.config.max_fee_percentage.get() {
+            return Err(MarketplaceError::InvalidFeePercentage(InvalidFeePercentage{}));
+        }
+
+        if new_fee_percentage > self.platform_fee_percentage.get() {
+            let execute_after = U256::from(self.vm().block_timestamp() + FEE_CHANGE_DELAY);
+            self.pending_platform_fee.set(new_fee_percentage);
+            self.pending_platform_fee_eta.set(execute_after);
+
+            log(self.vm(), PlatformFeeChangeQueued {
+                newFeePercentage: new_fee_percentage,
+                executeAfter: execute_after,
+            });
+
+            return Ok(());
+        }
+
+        let previous_fee = self.platform_fee_percentage.get();
+        self.platform_fee_percentage.set(new_fee_percentage);
+
+        // Emit event
+        log(self.vm(), PlatformFeeUpdated {
+            previousFeePercentage: previous_fee,
+            newFeePercentage: new_fee_percentage,
+        });
+
+        Ok(())
+    }
+
+    fn execute_platform_fee_change_inner(&mut self) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        let execute_after = self.pending_platform_fee_eta.get();
+        if execute_after == U256::ZERO {
+            return Err(MarketplaceError::NoPendingFeeChange(NoPendingFeeChange{}));
+        }
+
+        if U256::from(self.vm().block_timestamp()) < execute_after {
+            return Err(MarketplaceError::FeeChangeTimelocked(FeeChangeTimelocked{}));
+        }
+
+        let previous_fee = self.platform_fee_percentage.get();
+        let new_fee = self.pending_platform_fee.get();
+
+        self.platform_fee_percentage.set(new_fee);
+        self.pending_platform_fee.set(U256::ZERO);
+        self.pending_platform_fee_eta.set(U256::ZERO);
+
+        log(self.vm(), PlatformFeeChangeExecuted {
+            previousFeePercentage: previous_fee,
+            newFeePercentage: new_fee,
+        });
+
+        Ok(())
+    }
+
+    fn cancel_platform_fee_change_inner(&mut self) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if self.pending_platform_fee_eta.get() == U256::ZERO {
+            return Err(MarketplaceError::NoPendingFeeChange(NoPendingFeeChange{}));
+        }
+
+        let canceled_fee = self.pending_platform_fee.get();
+        self.pending_platform_fee.set(U256::ZERO);
+        self.pending_platform_fee_eta.set(U256::ZERO);
+
+        log(self.vm(), PlatformFeeChangeCanceled {
+            newFeePercentage: canceled_fee,
+        });
+
+        Ok(())
+    }
+
+    fn transfer_platform_ownership_inner(&mut self, new_owner: Address) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        self.pending_platform_owner.set(new_owner);
+
+        log(self.vm(), PlatformOwnershipTransferStarted {
+            previousOwner: self.platform_owner.get(),
+            newOwner: new_owner,
+        });
+
+        Ok(())
+    }
+
+    fn accept_platform_ownership_inner(&mut self) -> Result<(), MarketplaceError> {
+        let sender = self.vm().msg_sender();
+        let pending_owner = self.pending_platform_owner.get();
+
+        if pending_owner == Address::ZERO || sender != pending_owner {
+            return Err(MarketplaceError::NotPendingPlatformOwner(NotPendingPlatformOwner{}));
+        }
+
+        let previous_owner = self.platform_owner.get();
+        self.platform_owner.set(sender);
+        self.pending_platform_owner.set(Address::ZERO);
+
+        log(self.vm(), PlatformOwnershipTransferred {
+            previousOwner: previous_owner,
+            newOwner: sender,
+        });
+
+        Ok(())
+    }
+
+    fn update_fee_recipient_inner(&mut self, new_recipient: Address) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if new_recipient == Address::ZERO {
+            return Err(MarketplaceError::InvalidFeeRecipient(InvalidFeeRecipient{}));
+        }
+
+        let previous_recipient = self.current_fee_recipient();
+        self.fee_recipient.set(new_recipient);
+
+        log(self.vm(), FeeRecipientUpdated {
+            previousRecipient: previous_recipient,
+            newRecipient: new_recipient,
+        });
+
+        Ok(())
+    }
+
+    fn set_fee_payees_inner(&mut self, payees: Vec<(Address, U256)>) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if payees.len() > MAX_FEE_PAYEES {
+            return Err(MarketplaceError::InvalidFeePayees(InvalidFeePayees{}));
+        }
+
+        let mut total_shares = U256::ZERO;
+        for &(account, share_bps) in &payees {
+            // Each share is capped first so the running total can't wrap
+            if account == Address::ZERO || share_bps == U256::ZERO || share_bps > U256::from(TOTAL_SHARE_BPS) {
+                return Err(MarketplaceError::InvalidFeePayees(InvalidFeePayees{}));
+            }
+            total_shares += share_bps;
+        }
+
+        if !payees.is_empty() && total_shares != U256::from(TOTAL_SHARE_BPS) {
+            return Err(MarketplaceError::InvalidFeePayees(InvalidFeePayees{}));
+        }
+
+        self.fee_payees.erase();
+        for &(account, share_bps) in &payees {
+            let mut payee = self.fee_payees.grow();
+            payee.account.set(account);
+            payee.share_bps.set(share_bps);
+        }
+
+        log(self.vm(), FeePayeesUpdated {
+            accounts: payees.iter().map(|&(account, _)| account).collect(),
+            sharesBps: payees.iter().map(|&(_, share_bps)| share_bps).collect(),
+        });
+
+        Ok(())
+    }
+
+    fn set_account_blocked_inner(&mut self, account: Address, blocked: bool) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        self.blocked_accounts.insert(account, blocked);
+
+        log(self.vm(), AccountBlocklistUpdated { account, blocked });
+
+        Ok(())
+    }
+
+    fn set_duration_bounds_inner(&mut self, min_duration: U256, max_duration: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        // The cap keeps `now + duration` far from overflowing
+        if min_duration == U256::ZERO || min_duration > max_duration || max_duration > U256::from(MAX_DURATION_CAP) {
+            return Err(MarketplaceError::InvalidDuration(InvalidDuration{}));
+        }
+
+        let config = &mut self.config;
+        config.min_duration.set(min_duration);
+        config.max_duration.set(max_duration);
+
+        log(self.vm(), DurationBoundsUpdated {
+            minDuration: min_duration,
+            maxDuration: max_duration,
+        });
+
+        Ok(())
+    }
+
+    fn set_max_fee_percentage_inner(&mut self, max_fee_percentage: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        // Leave room for the largest creator share so proceeds can't underflow
+        if max_fee_percentage > U256::from(TOTAL_SHARE_BPS - MAX_CREATOR_SHARE_BPS)
+            || max_fee_percentage < self.platform_fee_percentage.get()
+            || max_fee_percentage < self.pending_platform_fee.get()
+        {
+            return Err(MarketplaceError::InvalidFeePercentage(InvalidFeePercentage{}));
+        }
+
+        let previous_max = self.config.max_fee_percentage.get();
+        self.config.max_fee_percentage.set(max_fee_percentage);
+
+        log(self.vm(), MaxFeePercentageUpdated {
+            previousMaxFeePercentage: previous_max,
+            newMaxFeePercentage: max_fee_percentage,
+        });
+
+        Ok(())
+    }
+
+    fn set_min_bid_increment_inner(&mut self, increment_bps: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if increment_bps == U256::ZERO || increment_bps > U256::from(TOTAL_SHARE_BPS) {
+            return Err(MarketplaceError::InvalidBidIncrement(InvalidBidIncrement{}));
+        }
+
+        let previous_increment = self.config.min_bid_increment_bps.get();
+        self.config.min_bid_increment_bps.set(increment_bps);
+
+        log(self.vm(), MinBidIncrementUpdated {
+            previousIncrementBps: previous_increment,
+            newIncrementBps: increment_bps,
+        });
+
+        Ok(())
+    }
+
+    fn set_extension_window_inner(&mut self, window: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if window > U256::from(MAX_EXTENSION_WINDOW) {
+            return Err(MarketplaceError::InvalidExtensionWindow(InvalidExtensionWindow{}));
+        }
+
+        let previous_window = self.config.extension_window.get();
+        self.config.extension_window.set(window);
+
+        log(self.vm(), ExtensionWindowUpdated {
+            previousWindow: previous_window,
+            newWindow: window,
+        });
+
+        Ok(())
+    }
+
+    fn set_token_blocked_inner(&mut self, nft_contract: Address, token_id: U256, blocked: bool) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        self.blocked_tokens.setter(nft_contract).insert(token_id, blocked);
+
+        log(self.vm(), TokenBlocklistUpdated {
+            nftContract: nft_contract,
+            tokenId: token_id,
+            blocked,
+        });
+
+        Ok(())
+    }
+
+    fn set_bid_signer_inner(&mut self, signer: Address) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        let previous_signer = self.bid_signer.get();
+        self.bid_signer.set(signer);
+
+        log(self.vm(), BidSignerUpdated {
+            previousSigner: previous_signer,
+            newSigner: signer,
+        });
+
+        Ok(())
+    }
+
+    fn set_referral_share_inner(&mut self, share_bps: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if share_bps > U256::from(MAX_REFERRAL_SHARE_BPS) {
+            return Err(MarketplaceError::InvalidReferralShare(InvalidReferralShare{}));
+        }
+
+        let previous_share = self.referral_share_bps.get();
+        self.referral_share_bps.set(share_bps);
+
+        log(self.vm(), ReferralShareUpdated {
+            previousShareBps: previous_share,
+            newShareBps: share_bps,
+        });
+
+        Ok(())
+    }
+
+    fn set_curation_enabled_inner(&mut self, enabled: bool) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        self.curation_enabled.set(enabled);
+
+        log(self.vm(), CurationModeUpdated { enabled });
+
+        Ok(())
+    }
+
+    fn add_collection_inner(&mut self, nft_contract: Address) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        self.collections.setter(nft_contract).allowed.set(true);
+
+        log(self.vm(), CollectionAdded { nftContract: nft_contract });
+
+        Ok(())
+    }
+
+    fn remove_collection_inner(&mut self, nft_contract: Address) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if !self.collections.getter(nft_contract).allowed.get() {
+            return Err(MarketplaceError::CollectionNotAllowed(CollectionNotAllowed{}));
+        }
+
+        self.collections.setter(nft_contract).allowed.set(false);
+        self.change_collection_fee(nft_contract, false, U256::ZERO);
+
+        log(self.vm(), CollectionRemoved { nftContract: nft_contract });
+
+        Ok(())
+    }
+
+    fn set_collection_fee_inner(&mut self, nft_contract: Address, fee_percentage: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if !self.collections.getter(nft_contract).allowed.get() {
+            return Err(MarketplaceError::CollectionNotAllowed(CollectionNotAllowed{}));
+        }
+
+        // Same ceiling as the platform fee
+        if fee_percentage > self.config.max_fee_percentage.get() {
+            return Err(MarketplaceError::InvalidFeePercentage(InvalidFeePercentage{}));
+        }
+
+        self.change_collection_fee(nft_contract, true, fee_percentage);
+        Ok(())
+    }
+
+    fn clear_collection_fee_inner(&mut self, nft_contract: Address) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        self.change_collection_fee(nft_contract, false, U256::ZERO);
+        Ok(())
+    }
+
+    fn set_seller_fee_inner(&mut self, seller: Address, fee_percentage: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        // Same ceiling as the platform fee
+        if fee_percentage > self.config.max_fee_percentage.get() {
+            return Err(MarketplaceError::InvalidFeePercentage(InvalidFeePercentage{}));
+        }
+
+        self.change_seller_fee(seller, true, fee_percentage);
+        Ok(())
+    }
+
+    fn clear_seller_fee_inner(&mut self, seller: Address) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        self.change_seller_fee(seller, false, U256::ZERO);
+        Ok(())
+    }
+
+    fn pause_inner(&mut self) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if self.paused.get() {
+            return Err(MarketplaceError::ContractPaused(ContractPaused{}));
+        }
+
+        self.paused.set(true);
+
+        log(self.vm(), Paused {
+            account: self.vm().msg_sender(),
+        });
+
+        Ok(())
+    }
+
+    fn unpause_inner(&mut self) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if !self.paused.get() {
+            return Err(MarketplaceError::ContractNotPaused(ContractNotPaused{}));
+        }
+
+        self.paused.set(false);
+
+        log(self.vm(), Unpaused {
+            account: self.vm().msg_sender(),
+        });
+
+        Ok(())
+    }
+
+    fn emergency_cancel_auction_inner(&mut self, auction_id: U256) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        if !self.paused.get() {
+            return Err(MarketplaceError::ContractNotPaused(ContractNotPaused{}));
+        }
+
+        let (seller, bidder, refund_amount) = self.cancel_with_refund(auction_id)?;
+
+        log(self.vm(), AuctionEmergencyCanceled {
+            auctionId: auction_id,
+            seller,
+            bidder,
+            refundAmount: refund_amount,
+        });
+
+        Ok(())
+    }
+
+    fn force_cancel_auction_inner(&mut self, auction_id: U256, reason: u8) -> Result<(), MarketplaceError> {
+        self.only_platform_owner()?;
+
+        let (seller, bidder, refund_amount) = self.cancel_with_refund(auction_id)?;
+
+        log(self.vm(), AuctionForceCanceled {
+            auctionId: auction_id,
+            seller,
+            bidder,
+            refundAmount: refund_amount,
+            reason,
+        });
+
+        Ok(())
+    }
+
+    fn withdraw_inner(&mut self) -> Result<(), MarketplaceError> {
+        let sender = self.vm().msg_sender();
+        let balance = self.user_balances.getter(sender).get();
+
+        // Balances of blocklisted accounts are frozen
+        self.check_account_not_blocked(sender)?;
+
+        if balance == U256::ZERO {
+            return Err(MarketplaceError::InsufficientBalance(InsufficientBalance{}));
+        }
+
+        self.withdraw_balance(sender, balance)
+    }
+
+    fn withdraw_to_inner(&mut self, recipient: Address, amount: U256) -> Result<(), MarketplaceError> {
+        if recipient == Address::ZERO {
+            return Err(MarketplaceError::InvalidRecipient(InvalidRecipient{}));
+        }
+
+        if amount == U256::ZERO {
+            return Err(MarketplaceError::InvalidWithdrawalAmount(InvalidWithdrawalAmount{}));
+        }
+
+        self.withdraw_balance(recipient, amount)
+    }
+
+    fn on_erc1155_received_inner(
+        &mut self,
+        operator: Address,
+        from: Address,
+        id: U256,
+        value: U256,
+        data: Bytes,
+    ) -> Result<FixedBytes<4>, MarketplaceError> {
+        let (reserve_price, duration) = <(U256, U256)>::abi_decode(&data, true).map_err(|_| MarketplaceError::InvalidListingData(InvalidListingData{}))?;
+
+        let nft_contract = self.vm().msg_sender();
+        self.record_erc1155_deposit(nft_contract, operator, from, id, value)?;
+        self.create_erc1155_auction(nft_contract, from, id, value, reserve_price, duration)?;
+
+        Ok(FixedBytes::from(ERC1155_RECEIVED))
+    }
+
+    fn on_erc1155_batch_received_inner(
+        &mut self,
+        operator: Address,
+        from: Address,
+        ids: Vec<U256>,
+        values: Vec<U256>,
+        data: Bytes,
+    ) -> Result<FixedBytes<4>, MarketplaceError> {
+        if ids.len() != values.len() {
+            return Err(MarketplaceError::InvalidListingData(InvalidListingData{}));
+        }
+
+        let (reserve_price, duration) = <(U256, U256)>::abi_decode(&data, true).map_err(|_| MarketplaceError::InvalidListingData(InvalidListingData{}))?;
+
+        let nft_contract = self.vm().msg_sender();
+        for (id, value) in ids.into_iter().zip(values) {
+            self.record_erc1155_deposit(nft_contract, operator, from, id, value)?;
+            self.create_erc1155_auction(nft_contract, from, id, value, reserve_price, duration)?;
+        }
+
+        Ok(FixedBytes::from(ERC1155_BATCH_RECEIVED))
+    }
+}
+
+// Internal helpers
+impl NeonMarketplace {
+    fn only_platform_owner(&self) -> Result<(), MarketplaceError> {
+        if self.vm().msg_sender() != self.platform_owner.get() {
+            return Err(MarketplaceError::NotPlatformOwner(NotPlatformOwner{}));
+        }
+        Ok(())
+    }
+
+    /// Escrow an ERC721 NFT and open an auction for it
+    fn create_erc721_auction(
+        &mut self,
+        nft_contract: Address,
+        token_id: U256,
+        reserve_price: U256,
+        duration: U256,
+        creator: Address,
+        creator_share_bps: U256,
+    ) -> Result<U256, MarketplaceError> {
+        self.when_not_paused()?;
+
+        // Validate inputs
+        self.validate_auction_terms(reserve_price, duration)?;
+
+        if creator_share_bps > U256::from(MAX_CREATOR_SHARE_BPS) || (creator == Address::ZERO && creator_share_bps != U256::ZERO) {
+            return Err(MarketplaceError::InvalidCreatorShare(InvalidCreatorShare{}));
+        }
+
+        self.check_tradable(nft_contract, token_id, &[self.vm().msg_sender()])?;
+
+        // Check caller owns the NFT and has approved the marketplace
+        self.check_owner_and_approval(nft_contract, token_id, self.vm().msg_sender())?;
+
+        self.open_erc721_auction(nft_contract, token_id, reserve_price, duration, creator, creator_share_bps)
+    }
+
+    /// Record a validated ERC721 auction for `msg_sender` and escrow the NFT
+    fn open_erc721_auction(
+        &mut self,
+        nft_contract: Address,
+        token_id: U256,
+        reserve_price: U256,
+        duration: U256,
+        creator: Address,
+        creator_share_bps: U256,
+    ) -> Result<U256, MarketplaceError> {
+        let auction_id = self.next_auction_id.get();
+        let end_time = U256::from(self.vm().block_timestamp()) + duration;
+        let sender = self.vm().msg_sender();
+        let fee_percentage = self.fee_for(nft_contract, sender);
+
+        // Create auction
+        let mut auction = self.auctions.setter(auction_id);
+        auction.nft_contract.set(nft_contract);
+        auction.token_id.set(token_id);
+        auction.seller.set(sender);
+        auction.reserve_price.set(reserve_price);
+        auction.current_bid.set(U256::ZERO);
+        auction.current_bidder.set(Address::ZERO);
+        auction.end_time.set(end_time);
+        auction.settled.set(false);
+        auction.platform_fee_percentage.set(fee_percentage);
+        auction.referral_share_bps.set(self.referral_share_bps.get());
+        auction.creator.set(creator);
+        auction.creator_share_bps.set(creator_share_bps);
+
+        // Transfer NFT to contract
+        let transfer = IERC721::transferFromCall { from: self.vm().msg_sender(), to: self.vm().contract_address(), tokenId: token_id };
+        self.nft_call(nft_contract, transfer).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+
+        // Increment auction ID
+        self.next_auction_id.set(auction_id + U256::from(1));
+
+        // Emit event
+        log(self.vm(), AuctionCreated {
+            auctionId: auction_id,
+            nftContract: nft_contract,
+            tokenId: token_id,
+            seller: sender,
+            reservePrice: reserve_price,
+            duration,
+            endTime: end_time,
+            platformFeePercentage: fee_percentage,
+        });
+
+        if creator_share_bps > U256::ZERO {
+            log(self.vm(), AuctionCreatorShareSet {
+                auctionId: auction_id,
+                creator,
+                shareBps: creator_share_bps,
+            });
+        }
+
+        Ok(auction_id)
+    }
+
+    /// Record a bid of `msg_value` on an auction. Plain bids bid the full
+    /// amount; proxy bids escrow it as a ceiling and bid only what is needed.
+    fn bid(&mut self, auction_id: U256, referrer: Address, proxy: bool) -> Result<(), MarketplaceError> {
+        self.when_not_paused()?;
+
+        let auction = self.auctions.getter(auction_id);
+        let seller = auction.seller.get();
+
+        // Check if auction exists
+        if seller == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        // Check if auction is active
+        if U256::from(self.vm().block_timestamp()) >= auction.end_time.get() || auction.settled.get() {
+            return Err(MarketplaceError::AuctionNotActive(AuctionNotActive{}));
+        }
+
+        if self.vm().msg_sender() == seller {
+            return Err(MarketplaceError::SellerCannotBid(SellerCannotBid{}));
+        }
+
+        // A blocked seller or lot can't take new bids
+        self.check_lot_tradable(auction_id, &[seller, self.vm().msg_sender()])?;
+        self.check_bidder_allowed(auction_id, self.vm().msg_sender())?;
+
+        let bid_amount = self.vm().msg_value();
+        let current_bid = auction.current_bid.get();
+        let reserve_price = auction.reserve_price.get();
+        let previous_bidder = auction.current_bidder.get();
+        let previous_escrow = auction.max_bid.get().max(current_bid);
+        let sender = self.vm().msg_sender();
+
+        // The leader tops up their proxy ceiling without moving the price
+        if proxy && sender == previous_bidder {
+            if bid_amount == U256::ZERO {
+                return Err(MarketplaceError::BidTooLow(BidTooLow{}));
+            }
+
+            self.auctions.setter(auction_id).max_bid.set(previous_escrow + bid_amount);
+
+            log(self.vm(), ProxyBidIncreased {
+                auctionId: auction_id,
+                bidder: sender,
+            });
+
+            return Ok(());
+        }
+
+        // Check if bid meets minimum requirements
+        let min_bid = if current_bid == U256::ZERO {
+            reserve_price
+        } else {
+            self.min_next_bid(current_bid)
+        };
+
+        if bid_amount < min_bid {
+            return Err(MarketplaceError::BidTooLow(BidTooLow{}));
+        }
+
+        // A leading proxy bid whose ceiling covers this bid defends the lead
+        // (the earlier bidder wins ties) and the challenger is refunded
+        if previous_bidder != Address::ZERO && previous_bidder != sender && bid_amount <= previous_escrow {
+            let defended_bid = self.min_next_bid(bid_amount).min(previous_escrow);
+            self.auctions.setter(auction_id).current_bid.set(defended_bid);
+            self.extend_if_closing(auction_id);
+
+            self.refund_outbid_bidder(auction_id, sender, bid_amount);
+
+            log(self.vm(), ProxyBidRaised {
+                auctionId: auction_id,
+                bidder: previous_bidder,
+                challenger: sender,
+                amount: defended_bid,
+                challengerRefund: bid_amount,
+            });
+
+            return Ok(());
+        }
+
+        // Proxy bids only show what it takes to beat the previous ceiling
+        let visible_bid = if proxy && previous_bidder != Address::ZERO {
+            self.min_next_bid(previous_escrow).min(bid_amount).max(min_bid)
+        } else if proxy {
+            min_bid
+        } else {
+            bid_amount
+        };
+
+        // Previous bidder's full escrow is refunded once the new bid is recorded
+        let refund = if previous_bidder != Address::ZERO { previous_escrow } else { U256::ZERO };
+
+        // Bidders and sellers cannot refer themselves
+        let referrer = if referrer == sender || referrer == seller { Address::ZERO } else { referrer };
+
+        // Update auction with new bid
+        let mut auction_mut = self.auctions.setter(auction_id);
+        auction_mut.current_bid.set(visible_bid);
+        auction_mut.current_bidder.set(sender);
+        auction_mut.max_bid.set(bid_amount);
+        auction_mut.referrer.set(referrer);
+        self.extend_if_closing(auction_id);
+
+        if previous_bidder != Address::ZERO {
+            self.refund_outbid_bidder(auction_id, previous_bidder, refund);
+        }
+
+        // Emit event
+        log(self.vm(), BidPlaced {
+            auctionId: auction_id,
+            bidder: sender,
+            previousBidder: previous_bidder,
+            amount: visible_bid,
+            refund,
+        });
+
+        Ok(())
+    }
+
+    /// Send an outbid bidder's escrow straight back with a gas-limited call,
+    /// crediting `user_balances` instead if the transfer fails. Blocklisted
+    /// bidders are always credited so their funds stay frozen.
+    fn refund_outbid_bidder(&mut self, auction_id: U256, bidder: Address, amount: U256) {
+        if !self.blocked_accounts.get(bidder) {
+            let context = calls::context::Call::new().value(amount).gas(REFUND_GAS_LIMIT);
+            if self.vm().call(&context, bidder, &[]).is_ok() {
+                log(self.vm(), OutbidRefundSent {
+                    auctionId: auction_id,
+                    bidder,
+                    amount,
+                });
+                return;
+            }
+        }
+
+        let balance = self.user_balances.getter(bidder).get();
+        self.user_balances.setter(bidder).set(balance + amount);
+
+        log(self.vm(), OutbidRefundCredited {
+            auctionId: auction_id,
+            bidder,
+            amount,
+        });
+    }
+
+    /// Push the end of an auction back to `now + extension_window` when a bid
+    /// lands inside the window
+    fn extend_if_closing(&mut self, auction_id: U256) {
+        let window = self.config.extension_window.get();
+        if window == U256::ZERO {
+            return;
+        }
+
+        let new_end_time = U256::from(self.vm().block_timestamp()) + window;
+        if new_end_time <= self.auctions.getter(auction_id).end_time.get() {
+            return;
+        }
+
+        self.auctions.setter(auction_id).end_time.set(new_end_time);
+
+        log(self.vm(), AuctionExtended {
+            auctionId: auction_id,
+            newEndTime: new_end_time,
+        });
+    }
+
+    /// Sell a listing to `msg_sender` for `msg_value`
+    fn buy_listing(&mut self, listing_id: U256, referrer: Address) -> Result<(), MarketplaceError> {
+        self.when_not_paused()?;
+
+        let listing = self.listings.getter(listing_id);
+        let nft_contract = listing.nft_contract.get();
+        let token_id = listing.token_id.get();
+        let seller = listing.seller.get();
+        let price = listing.price.get();
+
+        if seller == Address::ZERO {
+            return Err(MarketplaceError::ListingNotFound(ListingNotFound{}));
+        }
+
+        if !listing.active.get() {
+            return Err(MarketplaceError::ListingNotActive(ListingNotActive{}));
+        }
+
+        if self.vm().msg_value() != price {
+            return Err(MarketplaceError::IncorrectPayment(IncorrectPayment{}));
+        }
+
+        // Collection, token and both parties must still be tradable, and the
+        // seller must still own the NFT and have approved the marketplace
+        self.check_tradable(nft_contract, token_id, &[seller, self.vm().msg_sender()])?;
+        self.check_listing_holdings(listing_id)?;
+
+        let listing = self.listings.getter(listing_id);
+        let (is_erc1155, amount) = (listing.is_erc1155.get(), listing.amount.get());
+
+        // Close the listing before the external call
+        self.listings.setter(listing_id).active.set(false);
+
+        let buyer = self.vm().msg_sender();
+        let transferred = if is_erc1155 {
+            let transfer = IERC1155::safeTransferFromCall { from: seller, to: buyer, id: token_id, value: amount, data: Vec::new().into() };
+            self.nft_call(nft_contract, transfer).map(|_| ())
+        } else {
+            self.nft_call(nft_contract, IERC721::transferFromCall { from: seller, to: buyer, tokenId: token_id }).map(|_| ())
+        };
+        transferred.map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+
+        let fee_percentage = self.fee_for(nft_contract, seller);
+        let referrer = if referrer == buyer || referrer == seller { Address::ZERO } else { referrer };
+        let referral_share_bps = self.referral_share_bps.get();
+        let (seller_proceeds, platform_fee, referral_fee, _) =
+            self.credit_sale(seller, price, fee_percentage, (referrer, referral_share_bps), NO_SHARE);
+
+        if referral_fee > U256::ZERO {
+            log(self.vm(), ReferralPaid {
+                referrer,
+                nftContract: nft_contract,
+                tokenId: token_id,
+                amount: referral_fee,
+            });
+        }
+
+        log(self.vm(), ListingSold {
+            listingId: listing_id,
+            buyer,
+            seller,
+            price,
+            sellerProceeds: seller_proceeds,
+            platformFee: platform_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Record an auction for ERC1155 units that have already been deposited
+    fn create_erc1155_auction(
+        &mut self,
+        nft_contract: Address,
+        seller: Address,
+        token_id: U256,
+        amount: U256,
+        reserve_price: U256,
+        duration: U256,
+    ) -> Result<U256, MarketplaceError> {
+        self.when_not_paused()?;
+
+        // Validate inputs
+        if amount == U256::ZERO {
+            return Err(MarketplaceError::InvalidAmount(InvalidAmount{}));
+        }
+
+        self.validate_auction_terms(reserve_price, duration)?;
+        self.check_tradable(nft_contract, token_id, &[seller])?;
+
+        let auction_id = self.next_auction_id.get();
+        let end_time = U256::from(self.vm().block_timestamp()) + duration;
+        let fee_percentage = self.fee_for(nft_contract, seller);
+
+        // Create auction
+        let mut auction = self.auctions.setter(auction_id);
+        auction.nft_contract.set(nft_contract);
+        auction.token_id.set(token_id);
+        auction.seller.set(seller);
+        auction.reserve_price.set(reserve_price);
+        auction.current_bid.set(U256::ZERO);
+        auction.current_bidder.set(Address::ZERO);
+        auction.end_time.set(end_time);
+        auction.settled.set(false);
+        auction.platform_fee_percentage.set(fee_percentage);
+        auction.referral_share_bps.set(self.referral_share_bps.get());
+        auction.is_erc1155.set(true);
+        auction.amount.set(amount);
+
+        // Increment auction ID
+        self.next_auction_id.set(auction_id + U256::from(1));
+
+        log(self.vm(), Erc1155AuctionCreated {
+            auctionId: auction_id,
+            nftContract: nft_contract,
+            tokenId: token_id,
+            seller,
+            amount,
+            reservePrice: reserve_price,
+            duration,
+            endTime: end_time,
+            platformFeePercentage: fee_percentage,
+        });
+
+        Ok(auction_id)
+    }
+
+    /// Validate reserve price and duration for a new auction
+    fn validate_auction_terms(&self, reserve_price: U256, duration: U256) -> Result<(), MarketplaceError> {
+        if reserve_price == U256::ZERO {
+            return Err(MarketplaceError::InvalidReservePrice(InvalidReservePrice{}));
+        }
+
+        if duration == U256::ZERO || duration < self.config.min_duration.get() || duration > self.config.max_duration.get() {
+            return Err(MarketplaceError::InvalidDuration(InvalidDuration{}));
+        }
+
+        Ok(())
+    }
+
+    /// Reject trades of a non-curated collection or a blocklisted token, or
+    /// involving a blocklisted account
+    fn check_tradable(&self, nft_contract: Address, token_id: U256, accounts: &[Address]) -> Result<(), MarketplaceError> {
+        self.check_collection_allowed(nft_contract)?;
+
+        if self.blocked_tokens.getter(nft_contract).get(token_id) {
+            return Err(MarketplaceError::TokenBlocked(TokenBlocked{}));
+        }
+
+        for &account in accounts {
+            self.check_account_not_blocked(account)?;
+        }
+        Ok(())
+    }
+
+    /// Reject moving an auction's escrow if any lot item or account is blocklisted
+    fn check_lot_tradable(&self, auction_id: U256, accounts: &[Address]) -> Result<(), MarketplaceError> {
+        for (nft_contract, token_id) in self.lot_items(auction_id) {
+            if self.blocked_tokens.getter(nft_contract).get(token_id) {
+                return Err(MarketplaceError::TokenBlocked(TokenBlocked{}));
+            }
+        }
+
+        for &account in accounts {
+            self.check_account_not_blocked(account)?;
+        }
+        Ok(())
+    }
+
+    fn check_account_not_blocked(&self, account: Address) -> Result<(), MarketplaceError> {
+        if self.blocked_accounts.get(account) {
+            return Err(MarketplaceError::AccountBlocked(AccountBlocked{}));
+        }
+        Ok(())
+    }
+
+    /// Reject collections outside the allowlist while curation is on
+    fn check_collection_allowed(&self, nft_contract: Address) -> Result<(), MarketplaceError> {
+        if self.curation_enabled.get() && !self.collections.getter(nft_contract).allowed.get() {
+            return Err(MarketplaceError::CollectionNotAllowed(CollectionNotAllowed{}));
+        }
+        Ok(())
+    }
+
+    /// Fee for a sale of `nft_contract` by `seller`, resolved in priority order:
+    /// seller tier, collection override, platform fee
+    fn fee_for(&self, nft_contract: Address, seller: Address) -> U256 {
+        // Overrides set under a higher ceiling are held to the current one
+        self.seller_fee(seller)
+            .or_else(|| self.collection_fee(nft_contract))
+            .unwrap_or(self.platform_fee_percentage.get())
+            .min(self.config.max_fee_percentage.get())
+    }
+
+    fn seller_fee(&self, seller: Address) -> Option<U256> {
+        let (has_override, fee_percentage) = self.seller_fee_override(seller);
+        has_override.then_some(fee_percentage)
+    }
+
+    fn collection_fee(&self, nft_contract: Address) -> Option<U256> {
+        let (has_override, fee_percentage) = self.collection_fee_override(nft_contract);
+        has_override.then_some(fee_percentage)
+    }
+
+    /// Seller override in force now as `(has_override, fee_percentage)`. A
+    /// queued change takes effect by itself once its delay has passed.
+    fn seller_fee_override(&self, seller: Address) -> (bool, U256) {
+        let seller_fee = self.seller_fees.getter(seller);
+        let eta = seller_fee.pending_fee_eta.get();
+
+        if eta != U256::ZERO && U256::from(self.vm().block_timestamp()) >= eta {
+            (seller_fee.pending_has_override.get(), seller_fee.pending_fee_percentage.get())
+        } else {
+            (seller_fee.has_fee_override.get(), seller_fee.fee_percentage.get())
+        }
+    }
+
+    /// Collection override in force now, resolved like `seller_fee_override`
+    fn collection_fee_override(&self, nft_contract: Address) -> (bool, U256) {
+        let collection = self.collections.getter(nft_contract);
+        let eta = collection.pending_fee_eta.get();
+
+        if eta != U256::ZERO && U256::from(self.vm().block_timestamp()) >= eta {
+            (collection.pending_has_override.get(), collection.pending_fee_percentage.get())
+        } else {
+            (collection.has_fee_override.get(), collection.fee_percentage.get())
+        }
+    }
+
+    /// Whether moving an override from `current` to `new` raises the fee it
+    /// charges. Without an override the platform fee applies.
+    fn raises_fee(&self, current: (bool, U256), new: (bool, U256)) -> bool {
+        let platform_fee = self.platform_fee_percentage.get();
+        let charged = |(has_override, fee_percentage): (bool, U256)| if has_override { fee_percentage } else { platform_fee };
+        charged(new) > charged(current)
+    }
+
+    /// Change a collection's fee override. Reductions apply now; increases are
+    /// queued for `FEE_CHANGE_DELAY`, replacing any earlier queued change.
+    fn change_collection_fee(&mut self, nft_contract: Address, has_override: bool, fee_percentage: U256) {
+        let current = self.collection_fee_override(nft_contract);
+        let raises = self.raises_fee(current, (has_override, fee_percentage));
+        let execute_after = U256::from(self.vm().block_timestamp() + FEE_CHANGE_DELAY);
+
+        let mut collection = self.collections.setter(nft_contract);
+        let (current_has_override, current_fee) = if raises { current } else { (has_override, fee_percentage) };
+        collection.has_fee_override.set(current_has_override);
+        collection.fee_percentage.set(current_fee);
+        collection.pending_has_override.set(raises && has_override);
+        collection.pending_fee_percentage.set(if raises { fee_percentage } else { U256::ZERO });
+        collection.pending_fee_eta.set(if raises { execute_after } else { U256::ZERO });
+
+        if raises {
+            log(self.vm(), CollectionFeeChangeQueued {
+                nftContract: nft_contract,
+                hasOverride: has_override,
+                feePercentage: fee_percentage,
+                executeAfter: execute_after,
+            });
+        } else {
+            log(self.vm(), CollectionFeeUpdated {
+                nftContract: nft_contract,
+                hasOverride: has_override,
+                feePercentage: fee_percentage,
+            });
+        }
+    }
+
+    /// Change a seller's fee override, delayed like `change_collection_fee`
+    fn change_seller_fee(&mut self, seller: Address, has_override: bool, fee_percentage: U256) {
+        let current = self.seller_fee_override(seller);
+        let raises = self.raises_fee(current, (has_override, fee_percentage));
+        let execute_after = U256::from(self.vm().block_timestamp() + FEE_CHANGE_DELAY);
+
+        let mut seller_fee = self.seller_fees.setter(seller);
+        let (current_has_override, current_fee) = if raises { current } else { (has_override, fee_percentage) };
+        seller_fee.has_fee_override.set(current_has_override);
+        seller_fee.fee_percentage.set(current_fee);
+        seller_fee.pending_has_override.set(raises && has_override);
+        seller_fee.pending_fee_percentage.set(if raises { fee_percentage } else { U256::ZERO });
+        seller_fee.pending_fee_eta.set(if raises { execute_after } else { U256::ZERO });
+
+        if raises {
+            log(self.vm(), SellerFeeChangeQueued {
+                seller,
+                hasOverride: has_override,
+                feePercentage: fee_percentage,
+                executeAfter: execute_after,
+            });
+        } else {
+            log(self.vm(), SellerFeeUpdated {
+                seller,
+                hasOverride: has_override,
+                feePercentage: fee_percentage,
+            });
+        }
+    }
+
+    /// Static call a view function on an NFT contract and decode its result
+    fn nft_view<C: SolCall>(&self, nft_contract: Address, call: C) -> Result<C::Return, calls::errors::Error> {
+        let output = self.vm().static_call(&calls::context::Call::new(), nft_contract, &call.abi_encode())?;
+        Ok(C::abi_decode_returns(&output, true)?)
+    }
+
+    /// Call a state-changing function on an NFT contract and decode its result
+    fn nft_call<C: SolCall>(&mut self, nft_contract: Address, call: C) -> Result<C::Return, calls::errors::Error> {
+        let output = self.vm().call(&calls::context::Call::new(), nft_contract, &call.abi_encode())?;
+        Ok(C::abi_decode_returns(&output, true)?)
+    }
+
+    /// Check `expected_owner` owns the NFT and the marketplace may transfer it
+    fn check_owner_and_approval(&self, nft_contract: Address, token_id: U256, expected_owner: Address) -> Result<(), MarketplaceError> {
+        // Check ownership using static call
+        let owner = self.nft_view(nft_contract, IERC721::ownerOfCall { tokenId: token_id }).map_err(|_| MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))?._0;
+
+        if owner != expected_owner {
+            return Err(MarketplaceError::NotTokenOwner(NotTokenOwner{}));
+        }
+
+        // Check if marketplace is approved to transfer this NFT
+        let operator = self.vm().contract_address();
+        let approved = self.nft_view(nft_contract, IERC721::getApprovedCall { tokenId: token_id }).map_err(|_| MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))?._0;
+        let is_approved_for_all = self.nft_view(nft_contract, IERC721::isApprovedForAllCall { owner: expected_owner, operator }).map_err(|_| MarketplaceError::ERC721InvalidTokenId(ERC721InvalidTokenId{}))?._0;
+
+        if approved != self.vm().contract_address() && !is_approved_for_all {
+            return Err(MarketplaceError::NotApprovedForTransfer(NotApprovedForTransfer{}));
+        }
+
+        Ok(())
+    }
+
+    /// Check `owner` holds at least `amount` ERC1155 units and has approved the marketplace
+    fn check_erc1155_balance_and_approval(&self, nft_contract: Address, token_id: U256, owner: Address, amount: U256) -> Result<(), MarketplaceError> {
+        let balance = self.nft_view(nft_contract, IERC1155::balanceOfCall { account: owner, id: token_id }).map_err(|_| MarketplaceError::NotTokenOwner(NotTokenOwner{}))?._0;
+
+        if balance < amount {
+            return Err(MarketplaceError::NotTokenOwner(NotTokenOwner{}));
+        }
+
+        let operator = self.vm().contract_address();
+        let approved = self.nft_view(nft_contract, IERC1155::isApprovedForAllCall { account: owner, operator }).map_err(|_| MarketplaceError::NotApprovedForTransfer(NotApprovedForTransfer{}))?._0;
+
+        if !approved {
+            return Err(MarketplaceError::NotApprovedForTransfer(NotApprovedForTransfer{}));
+        }
+
+        Ok(())
+    }
+
+    /// Check a listing's seller still holds the listed token(s) and the marketplace may move them
+    fn check_listing_holdings(&self, listing_id: U256) -> Result<(), MarketplaceError> {
+        let listing = self.listings.getter(listing_id);
+        let (nft_contract, token_id, seller) = (listing.nft_contract.get(), listing.token_id.get(), listing.seller.get());
+
+        if listing.is_erc1155.get() {
+            self.check_erc1155_balance_and_approval(nft_contract, token_id, seller, listing.amount.get())
+        } else {
+            self.check_owner_and_approval(nft_contract, token_id, seller)
+        }
+    }
+
+    /// Verify an ERC1155 receiver hook call reports a real deposit and add it to
+    /// the escrow. The hooks are public, so the caller must be a contract, the
+    /// owner must have sent the units (never the marketplace itself), and our
+    /// balance must cover everything already escrowed plus `value`. Hooks
+    /// triggered by the marketplace's own transfers also hit the reentrancy lock.
+    fn record_erc1155_deposit(&mut self, nft_contract: Address, operator: Address, from: Address, id: U256, value: U256) -> Result<(), MarketplaceError> {
+        let contract_addr = self.vm().contract_address();
+
+        if self.vm().code_size(nft_contract) == 0 || operator != from || operator == contract_addr {
+            return Err(MarketplaceError::InvalidDeposit(InvalidDeposit{}));
+        }
+
+        let escrowed = self.erc1155_escrow.getter(nft_contract).get(id).checked_add(value).ok_or(MarketplaceError::InvalidDeposit(InvalidDeposit{}))?;
+        let balance = self.nft_view(nft_contract, IERC1155::balanceOfCall { account: contract_addr, id }).map_err(|_| MarketplaceError::InvalidDeposit(InvalidDeposit{}))?._0;
+
+        if balance < escrowed {
+            return Err(MarketplaceError::InvalidDeposit(InvalidDeposit{}));
+        }
+
+        self.erc1155_escrow.setter(nft_contract).insert(id, escrowed);
+        Ok(())
+    }
+
+    /// `(nft_contract, token_id)` items sold in an auction's lot
+    fn lot_items(&self, auction_id: U256) -> Vec<(Address, U256)> {
+        let auction = self.auctions.getter(auction_id);
+
+        if !auction.is_bundle.get() {
+            return vec![(auction.nft_contract.get(), auction.token_id.get())];
+        }
+
+        let mut items = Vec::with_capacity(auction.bundle_items.len());
+        for i in 0..auction.bundle_items.len() {
+            if let Some(item) = auction.bundle_items.get(i) {
+                items.push((item.nft_contract.get(), item.token_id.get()));
+            }
+        }
+        items
+    }
+
+    /// Transfer every escrowed NFT of an auction's lot to `to`, reverting if any item fails
+    fn transfer_lot(&mut self, auction_id: U256, to: Address) -> Result<(), MarketplaceError> {
+        let contract_addr = self.vm().contract_address();
+
+        let auction = self.auctions.getter(auction_id);
+        if auction.is_erc1155.get() {
+            let token = auction.nft_contract.get();
+            let (id, amount) = (auction.token_id.get(), auction.amount.get());
+            let transfer = IERC1155::safeTransferFromCall {
+                from: contract_addr,
+                to,
+                id,
+                value: amount,
+                data: Vec::new().into(),
+            };
+            self.nft_call(token, transfer).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+
+            let escrowed = self.erc1155_escrow.getter(token).get(id);
+            self.erc1155_escrow.setter(token).insert(id, escrowed.saturating_sub(amount));
+            return Ok(());
+        }
+
+        for (nft_contract, token_id) in self.lot_items(auction_id) {
+            self.nft_call(nft_contract, IERC721::transferFromCall { from: contract_addr, to, tokenId: token_id }).map_err(|_| MarketplaceError::TransferFailed(TransferFailed{}))?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether `recipient` is a contract whose ERC1155 receiver hook rejects
+    /// an ERC1155 lot, checked by static-calling the hook as a transfer would
+    fn rejects_erc1155_lot(&self, auction_id: U256, recipient: Address) -> bool {
+        let auction = self.auctions.getter(auction_id);
+        if !auction.is_erc1155.get() || self.vm().code_size(recipient) == 0 {
+            return false;
+        }
+
+        let contract_addr = self.vm().contract_address();
+        let hook = IERC1155Receiver::onERC1155ReceivedCall {
+            operator: contract_addr,
+            from: contract_addr,
+            id: auction.token_id.get(),
+            value: auction.amount.get(),
+            data: Vec::new().into(),
+        };
+        self.nft_view(recipient, hook).map(|selector| selector._0 != ERC1155_RECEIVED).unwrap_or(true)
+    }
+
+    /// Validate and record an auction's bidder gate
+    fn apply_auction_gate(&mut self, auction_id: U256, gate_kind: u8, merkle_root: B256, collection: Address) -> Result<(), MarketplaceError> {
+        let valid = match gate_kind {
+            GATE_NONE | GATE_SIGNATURE => true,
+            GATE_MERKLE => merkle_root != B256::ZERO,
+            GATE_HOLDER => collection != Address::ZERO,
+            _ => false,
+        };
+        if !valid {
+            return Err(MarketplaceError::InvalidGate(InvalidGate{}));
+        }
+
+        let merkle_root = if gate_kind == GATE_MERKLE { merkle_root } else { B256::ZERO };
+        let collection = if gate_kind == GATE_HOLDER { collection } else { Address::ZERO };
+
+        // Approvals were granted under the old gate and don't carry over
+        let mut auction_mut = self.auctions.setter(auction_id);
+        let gate_epoch = auction_mut.gate_epoch.get() + U256::from(1);
+        auction_mut.gate_epoch.set(gate_epoch);
+        auction_mut.gate_kind.set(U8::from(gate_kind));
+        auction_mut.gate_merkle_root.set(merkle_root);
+        auction_mut.gate_collection.set(collection);
+
+        log(self.vm(), AuctionGateSet {
+            auctionId: auction_id,
+            gateKind: gate_kind,
+            merkleRoot: merkle_root,
+            collection,
+        });
+
+        Ok(())
+    }
+
+    /// Check `bidder` may bid on an auction under its gate
+    fn check_bidder_allowed(&self, auction_id: U256, bidder: Address) -> Result<(), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        let allowed = match auction.gate_kind.get().to::<u8>() {
+            GATE_NONE => true,
+            GATE_HOLDER => {
+                self.nft_view(auction.gate_collection.get(), IERC721::balanceOfCall { owner: bidder })
+                    .map(|balance| balance._0 > U256::ZERO)
+                    .unwrap_or(false)
+            }
+            _ => auction.approved_bidders.getter(auction.gate_epoch.get()).get(bidder),
+        };
+
+        if !allowed {
+            return Err(MarketplaceError::BidderNotAllowed(BidderNotAllowed{}));
+        }
+        Ok(())
+    }
+
+    /// Record the caller as an approved bidder after checking their Merkle
+    /// proof or bid signer authorization against the auction's gate
+    fn approve_bidder(&mut self, auction_id: U256, proof: &[B256], signature: &[u8]) -> Result<(), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        let bidder = self.vm().msg_sender();
+        let allowed = match auction.gate_kind.get().to::<u8>() {
+            GATE_MERKLE => {
+                // OpenZeppelin StandardMerkleTree leaf: keccak256(bytes.concat(keccak256(abi.encode(bidder))))
+                let leaf = keccak(keccak(bidder.abi_encode()));
+                verify_merkle_proof(auction.gate_merkle_root.get(), leaf, proof)
+            }
+            GATE_SIGNATURE => {
+                let signer = self.bid_signer.get();
+                let digest = self.bid_authorization_digest(auction_id, bidder);
+                signer != Address::ZERO && self.recover_signer(digest, signature)? == signer
+            }
+            // Open and holder gated auctions need no approval
+            _ => return Ok(()),
+        };
+
+        if !allowed {
+            return Err(MarketplaceError::BidderNotAllowed(BidderNotAllowed{}));
+        }
+
+        let mut auction_mut = self.auctions.setter(auction_id);
+        let gate_epoch = auction_mut.gate_epoch.get();
+        auction_mut.approved_bidders.setter(gate_epoch).insert(bidder, true);
+
+        log(self.vm(), BidderApproved {
+            auctionId: auction_id,
+            bidder,
+        });
+
+        Ok(())
+    }
+
+    /// Pay `amount` of `msg_sender`'s balance to `recipient`, restoring it if
+    /// the transfer fails
+    fn withdraw_balance(&mut self, recipient: Address, amount: U256) -> Result<(), MarketplaceError> {
+        let sender = self.vm().msg_sender();
+        let balance = self.user_balances.getter(sender).get();
+
+        // Balances of blocklisted accounts are frozen
+        self.check_account_not_blocked(sender)?;
+        self.check_account_not_blocked(recipient)?;
+
+        if amount > balance {
+            return Err(MarketplaceError::InsufficientBalance(InsufficientBalance{}));
+        }
+
+        // Reduce balance before transfer (reentrancy protection)
+        let remaining_balance = balance - amount;
+        self.user_balances.setter(sender).set(remaining_balance);
+
+        // Transfer funds using vm().transfer_eth
+        match self.vm().transfer_eth(recipient, amount) {
+            Ok(_) => {
+                log(self.vm(), FundsWithdrawn {
+                    user: sender,
+                    recipient,
+                    amount,
+                    remainingBalance: remaining_balance,
+                });
+                Ok(())
+            }
+            Err(_) => {
+                // Restore balance on failed transfer
+                self.user_balances.setter(sender).set(balance);
+                Err(MarketplaceError::TransferFailed(TransferFailed{}))
+            }
+        }
+    }
+
+    /// Named auction fields plus status, minimum next bid and time remaining
+    fn auction_view(&self, auction_id: U256) -> Result<AuctionView, MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+
+        if auction.seller.get() == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        let now = U256::from(self.vm().block_timestamp());
+        let end_time = auction.end_time.get();
+        let current_bid = auction.current_bid.get();
+
+        let status = if auction.cancelled.get() {
+            STATUS_CANCELLED
+        } else if auction.settled.get() {
+            STATUS_SETTLED
+        } else if now >= end_time {
+            STATUS_ENDED
+        } else {
+            STATUS_ACTIVE
+        };
+
+        let min_next_bid = if current_bid == U256::ZERO {
+            auction.reserve_price.get()
+        } else {
+            self.min_next_bid(current_bid)
+        };
+
+        Ok(AuctionView {
+            auctionId: auction_id,
+            nftContract: auction.nft_contract.get(),
+            tokenId: auction.token_id.get(),
+            seller: auction.seller.get(),
+            reservePrice: auction.reserve_price.get(),
+            currentBid: current_bid,
+            currentBidder: auction.current_bidder.get(),
+            endTime: end_time,
+            settled: auction.settled.get(),
+            isBundle: auction.is_bundle.get(),
+            isErc1155: auction.is_erc1155.get(),
+            amount: self.lot_amount(auction_id),
+            status,
+            minNextBid: min_next_bid,
+            timeRemaining: end_time.saturating_sub(now),
+        })
+    }
+
+    /// Units of the token an auction sells: its ERC1155 amount, or 1 for ERC721 lots
+    fn lot_amount(&self, auction_id: U256) -> U256 {
+        let auction = self.auctions.getter(auction_id);
+        if auction.is_erc1155.get() {
+            auction.amount.get()
+        } else {
+            U256::from(1)
+        }
+    }
+
+    /// Lowest bid that can follow `bid`: the configured increment above it
+    fn min_next_bid(&self, bid: U256) -> U256 {
+        bid + bid * self.config.min_bid_increment_bps.get() / U256::from(TOTAL_SHARE_BPS)
+    }
+
+    /// Credit the winner with the escrow a proxy bid did not need
+    fn refund_proxy_surplus(&mut self, auction_id: U256) {
+        let auction = self.auctions.getter(auction_id);
+        let winner = auction.current_bidder.get();
+        let current_bid = auction.current_bid.get();
+        let max_bid = auction.max_bid.get();
+
+        if max_bid <= current_bid {
+            return;
+        }
+
+        let surplus = max_bid - current_bid;
+        self.auctions.setter(auction_id).max_bid.set(current_bid);
+
+        let winner_balance = self.user_balances.getter(winner).get();
+        self.user_balances.setter(winner).set(winner_balance + surplus);
+
+        log(self.vm(), ProxyEscrowRefunded {
+            auctionId: auction_id,
+            bidder: winner,
+            amount: surplus,
+        });
+    }
+
+    /// Split the winning bid into seller proceeds and platform fee, using the
+    /// fee percentage and referral share snapshotted at creation, and pay any
+    /// referrer and creator
+    fn credit_sale_proceeds(&mut self, auction_id: U256) -> (U256, U256) {
+        let auction = self.auctions.getter(auction_id);
+        let seller = auction.seller.get();
+        let current_bid = auction.current_bid.get();
+        let fee_percentage = auction.platform_fee_percentage.get();
+        let nft_contract = auction.nft_contract.get();
+        let token_id = auction.token_id.get();
+        let referrer = auction.referrer.get();
+        let referral_share_bps = auction.referral_share_bps.get();
+        let creator = auction.creator.get();
+        let creator_share_bps = auction.creator_share_bps.get();
+
+        let (seller_amount, platform_fee, referral_fee, creator_fee) =
+            self.credit_sale(seller, current_bid, fee_percentage, (referrer, referral_share_bps), (creator, creator_share_bps));
+
+        if referral_fee > U256::ZERO {
+            log(self.vm(), ReferralPaid {
+                referrer,
+                nftContract: nft_contract,
+                tokenId: token_id,
+                amount: referral_fee,
+            });
+        }
+
+        if creator_fee > U256::ZERO {
+            log(self.vm(), CreatorFeePaid {
+                auctionId: auction_id,
+                creator,
+                amount: creator_fee,
+            });
+        }
+
+        (seller_amount, platform_fee)
+    }
+
+    /// Credit a sale's proceeds to the seller and its fee to the fee recipient.
+    /// The referrer's cut comes out of the platform fee and the creator's out
+    /// of the seller's proceeds, each given as `(account, share_bps)`; pass
+    /// `NO_SHARE` to skip either.
+    /// Returns `(seller_proceeds, platform_fee, referral_fee, creator_fee)`.
+    fn credit_sale(
+        &mut self,
+        seller: Address,
+        amount: U256,
+        fee_percentage: U256,
+        (referrer, referral_share_bps): (Address, U256),
+        (creator, creator_share_bps): (Address, U256),
+    ) -> (U256, U256, U256, U256) {
+        let total_fee = (amount * fee_percentage) / U256::from(10000);
+
+        let referral_fee = if referrer != Address::ZERO {
+            total_fee * referral_share_bps / U256::from(10000)
+        } else {
+            U256::ZERO
+        };
+        let creator_fee = if creator != Address::ZERO {
+            amount * creator_share_bps / U256::from(10000)
+        } else {
+            U256::ZERO
+        };
+
+        let platform_fee = total_fee - referral_fee;
+        let seller_amount = amount - total_fee - creator_fee;
+
+        // Add seller proceeds to withdrawable balance
+        let seller_balance = self.user_balances.getter(seller).get();
+        self.user_balances.setter(seller).set(seller_balance + seller_amount);
+
+        for (recipient, share) in [(referrer, referral_fee), (creator, creator_fee)] {
+            if share > U256::ZERO {
+                let balance = self.user_balances.getter(recipient).get();
+                self.user_balances.setter(recipient).set(balance + share);
+            }
+        }
+
+        self.credit_platform_fee(platform_fee);
+
+        (seller_amount, platform_fee, referral_fee, creator_fee)
+    }
+
+    /// Credit a platform fee to the fee payees pro rata, or to the fee
+    /// recipient when no split is configured. Rounding dust goes to the
+    /// first payee so the full fee is always credited.
+    fn credit_platform_fee(&mut self, platform_fee: U256) {
+        let payee_count = self.fee_payees.len();
+        if payee_count == 0 {
+            let fee_recipient = self.current_fee_recipient();
+            let recipient_balance = self.user_balances.getter(fee_recipient).get();
+            self.user_balances.setter(fee_recipient).set(recipient_balance + platform_fee);
+            return;
+        }
+
+        let mut shares = Vec::with_capacity(payee_count);
+        let mut distributed = U256::ZERO;
+        for i in 0..payee_count {
+            if let Some(payee) = self.fee_payees.getter(i) {
+                let share = platform_fee * payee.share_bps.get() / U256::from(TOTAL_SHARE_BPS);
+                distributed += share;
+                shares.push((payee.account.get(), share));
+            }
+        }
+
+        if let Some(first) = shares.first_mut() {
+            first.1 += platform_fee - distributed;
+        }
+
+        for (account, share) in shares {
+            let balance = self.user_balances.getter(account).get();
+            self.user_balances.setter(account).set(balance + share);
+        }
+    }
+
+    /// EIP-712 domain separator, bound to this chain and contract
+    fn domain_separator(&self) -> B256 {
+        keccak((
+            keccak(EIP712_DOMAIN_TYPE),
+            keccak(DOMAIN_NAME),
+            keccak(DOMAIN_VERSION),
+            U256::from(self.vm().chain_id()),
+            self.vm().contract_address(),
+        ).abi_encode())
+    }
+
+    /// EIP-712 typed data digest for an order
+    fn order_digest(&self, order: &Order) -> B256 {
+        let struct_hash = keccak((
+            keccak(ORDER_TYPE),
+            order.seller,
+            order.nftContract,
+            order.tokenId,
+            order.price,
+            order.currency,
+            order.expiry,
+            order.nonce,
+        ).abi_encode());
+
+        self.typed_data_digest(struct_hash)
+    }
+
+    /// EIP-712 typed data digest authorizing `bidder` on a signature gated auction
+    fn bid_authorization_digest(&self, auction_id: U256, bidder: Address) -> B256 {
+        let struct_hash = keccak((
+            keccak(BID_AUTHORIZATION_TYPE),
+            auction_id,
+            bidder,
+        ).abi_encode());
+
+        self.typed_data_digest(struct_hash)
+    }
+
+    /// `keccak256("\x19\x01" || domainSeparator || structHash)`
+    fn typed_data_digest(&self, struct_hash: B256) -> B256 {
+        let mut digest_input = Vec::with_capacity(66);
+        digest_input.extend_from_slice(b"\x19\x01");
+        digest_input.extend_from_slice(self.domain_separator().as_slice());
+        digest_input.extend_from_slice(struct_hash.as_slice());
+        keccak(digest_input)
+    }
+
+    /// Recover the signer of `digest` from a 65-byte `r || s || v` signature
+    fn recover_signer(&self, digest: B256, signature: &[u8]) -> Result<Address, MarketplaceError> {
+        if signature.len() != 65 {
+            return Err(MarketplaceError::InvalidSignature(InvalidSignature{}));
+        }
+
+        let r = U256::from_be_slice(&signature[0..32]);
+        let s = U256::from_be_slice(&signature[32..64]);
+        let v = signature[64];
+
+        if s > SECP256K1_HALF_ORDER || (v != 27 && v != 28) {
+            return Err(MarketplaceError::InvalidSignature(InvalidSignature{}));
+        }
+
+        let input = (digest, U256::from(v), r, s).abi_encode();
+        let output = self.vm().static_call(&calls::context::Call::new(), ECRECOVER, &input).map_err(|_| MarketplaceError::InvalidSignature(InvalidSignature{}))?;
+
+        if output.len() != 32 {
+            return Err(MarketplaceError::InvalidSignature(InvalidSignature{}));
+        }
+
+        let signer = Address::from_slice(&output[12..32]);
+        if signer == Address::ZERO {
+            return Err(MarketplaceError::InvalidSignature(InvalidSignature{}));
+        }
+
+        Ok(signer)
+    }
+
+    /// Fee recipient, falling back to the platform owner if never set
+    fn current_fee_recipient(&self) -> Address {
+        let recipient = self.fee_recipient.get();
+        if recipient == Address::ZERO {
+            self.platform_owner.get()
+        } else {
+            recipient
+        }
+    }
+
+    /// Run a mutating entry point while holding the reentrancy lock.
+    /// Stylus already rejects reentrant calls unless built with the
+    /// `reentrant` feature; this lock keeps every entry point safe if it is.
+    fn non_reentrant<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, MarketplaceError>) -> Result<T, MarketplaceError> {
+        if self.locked.get() {
+            return Err(MarketplaceError::Reentrancy(Reentrancy{}));
+        }
+
+        self.locked.set(true);
+        let result = f(self);
+        self.locked.set(false);
+        result
+    }
+
+    /// Cancel an unsettled auction on the platform's authority: credit any
+    /// high bid back to its bidder and return the lot to the seller. A single
+    /// NFT the collection refuses to move is left for the seller to claim, so
+    /// the refund never depends on the NFT contract; bundles move atomically.
+    /// A lot whose seller or items are blocklisted stays in escrow, claimable
+    /// by the seller once unblocked. Returns `(seller, bidder, refund_amount)`.
+    fn cancel_with_refund(&mut self, auction_id: U256) -> Result<(Address, Address, U256), MarketplaceError> {
+        let auction = self.auctions.getter(auction_id);
+        let seller = auction.seller.get();
+        let escrowed_bid = auction.max_bid.get().max(auction.current_bid.get());
+        let current_bidder = auction.current_bidder.get();
+        let is_bundle = auction.is_bundle.get();
+
+        // Check if auction exists
+        if seller == Address::ZERO {
+            return Err(MarketplaceError::AuctionNotFound(AuctionNotFound{}));
+        }
+
+        // Check if already settled
+        if auction.settled.get() {
+            return Err(MarketplaceError::AuctionAlreadySettled(AuctionAlreadySettled{}));
+        }
+
+        // Mark auction as settled (cancelled)
+        let mut auction = self.auctions.setter(auction_id);
+        auction.settled.set(true);
+        auction.cancelled.set(true);
+
+        // Credit the escrowed high bid back to the bidder
+        let refund_amount = if current_bidder != Address::ZERO {
+            let bidder_balance = self.user_balances.getter(current_bidder).get();
+            self.user_balances.setter(current_bidder).set(bidder_balance + escrowed_bid);
+            escrowed_bid
+        } else {
+            U256::ZERO
+        };
+
+        // Return NFT(s) to seller, or leave them claimable: a blocked lot stays
+        // frozen, and a single NFT may refuse the transfer
+        if self.check_lot_tradable(auction_id, &[seller]).is_err() {
+            self.auctions.setter(auction_id).nft_claimant.set(seller);
+        } else if is_bundle {
+            self.transfer_lot(auction_id, seller)?;
+        } else if self.transfer_lot(auction_id, seller).is_err() {
+            self.auctions.setter(auction_id).nft_claimant.set(seller);
+
+            log(self.vm(), NftTransferFailed {
+                auctionId: auction_id,
+                recipient: seller,
+            });
+        }
+
+        Ok((seller, current_bidder, refund_amount))
+    }
+
+    fn when_not_paused(&self) -> Result<(), MarketplaceError> {
+        if self.paused.get() {
+            return Err(MarketplaceError::ContractPaused(ContractPaused{}));
+        }
+        Ok(())
+    }
+}
+
+/// ERC-165 interface id of `IMultiCollectionNFT`: the XOR of its selectors
+fn multi_collection_interface_id() -> FixedBytes<4> {
+    let selectors = [
+        IMultiCollectionNFT::tokenCollectionCall::SELECTOR,
+        IMultiCollectionNFT::getCollectionCall::SELECTOR,
+        IMultiCollectionNFT::balanceOfCollectionCall::SELECTOR,
+    ];
+    FixedBytes(selectors.iter().fold([0u8; 4], |id, selector| core::array::from_fn(|i| id[i] ^ selector[i])))
+}
+
+/// Verify an OpenZeppelin-style Merkle proof (sorted pair hashing)
+fn verify_merkle_proof(root: B256, leaf: B256, proof: &[B256]) -> bool {
+    let mut hash = leaf;
+    for node in proof {
+        let (left, right) = if hash <= *node { (hash, *node) } else { (*node, hash) };
+        hash = keccak([left.as_slice(), right.as_slice()].concat());
+    }
+    hash == root
+}
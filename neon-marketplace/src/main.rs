@@ -1,3 +1,9 @@
+#![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
+
+#[cfg(not(any(test, feature = "export-abi")))]
+#[no_mangle]
+pub extern "C" fn main() {}
+
 // Export ABI for the contract
 #[cfg(feature = "export-abi")]
 fn main() {
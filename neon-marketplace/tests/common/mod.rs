@@ -0,0 +1,12 @@
+use neon_marketplace::*;
+use stylus_sdk::testing::*;
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::{SolCall, SolValue};
+
+/// Mock an ERC721 where `owner` holds `token_id` and has approved the marketplace
+pub fn mock_erc721(vm: &TestVM, nft_contract: Address, token_id: U256, owner: Address) {
+    let marketplace = vm.contract_address();
+    vm.mock_static_call(nft_contract, IERC721::ownerOfCall { tokenId: token_id }.abi_encode(), Ok(owner.abi_encode()));
+    vm.mock_static_call(nft_contract, IERC721::getApprovedCall { tokenId: token_id }.abi_encode(), Ok(marketplace.abi_encode()));
+    vm.mock_static_call(nft_contract, IERC721::isApprovedForAllCall { owner, operator: marketplace }.abi_encode(), Ok(false.abi_encode()));
+}
@@ -1,6 +1,11 @@
+mod common;
+
+use common::mock_erc721;
 use neon_marketplace::*;
 use stylus_sdk::testing::*;
+use stylus_sdk::abi::Bytes;
 use alloy_primitives::{Address, U256};
+use alloy_sol_types::{SolCall, SolEvent, SolValue};
 
 fn setup() -> (TestVM, NeonMarketplace) {
     let vm = TestVM::default();
@@ -8,9 +13,40 @@ fn setup() -> (TestVM, NeonMarketplace) {
     (vm, contract)
 }
 
+/// Give an ERC1155 contract code and mock the marketplace holding enough units
+/// of ids 0..=16 to back receiver hook deposits
+fn mock_edition_deposits(vm: &TestVM, edition_contract: Address) {
+    vm.set_code(edition_contract, vec![0xfe]);
+    for id in 0..=16u64 {
+        let balance_of = IERC1155::balanceOfCall { account: vm.contract_address(), id: U256::from(id) };
+        vm.mock_static_call(edition_contract, balance_of.abi_encode(), Ok(U256::from(100).abi_encode()));
+    }
+}
+
+/// Create a 1000 wei reserve, one hour ERC721 auction for the current sender
+fn create_mocked_auction(vm: &TestVM, contract: &mut NeonMarketplace, nft_contract: Address, token_id: U256) -> U256 {
+    mock_erc721(vm, nft_contract, token_id, vm.msg_sender());
+    contract.create_auction(nft_contract, token_id, U256::from(1000), U256::from(3600)).unwrap()
+}
+
+/// ERC1155 deposit data listing the units at a 1000 wei reserve for one hour
+fn edition_terms() -> Bytes {
+    Bytes::from((U256::from(1000), U256::from(3600)).abi_encode())
+}
+
+/// Deposit `amount` units of `token_id` from `seller` as an ERC1155 auction on
+/// `edition_terms`. Leaves the edition contract as the sender
+fn create_edition_auction(vm: &TestVM, contract: &mut NeonMarketplace, edition_contract: Address, seller: Address, token_id: U256, amount: U256) -> U256 {
+    mock_edition_deposits(vm, edition_contract);
+    let auction_id = contract.get_next_auction_id().unwrap();
+    vm.set_sender(edition_contract);
+    contract.on_erc1155_received(seller, seller, token_id, amount, edition_terms()).unwrap();
+    auction_id
+}
+
 #[test]
 fn test_initialization() {
-    let (vm, mut contract) = setup();
+    let (_vm, mut contract) = setup();
 
     // Initialize contract with 5% platform fee
     assert!(contract.initialize(U256::from(500)).is_ok());
@@ -31,6 +67,7 @@ fn test_create_auction() {
     let token_id = U256::from(1);
     let reserve_price = U256::from(1000);
     let duration = U256::from(3600); // 1 hour
+    mock_erc721(&vm, nft_contract, token_id, vm.msg_sender());
 
     // Create auction
     let auction_id = contract.create_auction(
@@ -48,7 +85,7 @@ fn test_create_auction() {
     assert_eq!(auction.1, token_id); // token_id
     assert_eq!(auction.2, vm.msg_sender()); // seller
     assert_eq!(auction.3, reserve_price); // reserve_price
-    assert_eq!(auction.6, false); // settled
+    assert!(!auction.7); // settled
 }
 
 #[test]
@@ -58,6 +95,7 @@ fn test_invalid_auction_creation() {
 
     let nft_contract = Address::from([1u8; 20]);
     let token_id = U256::from(1);
+    mock_erc721(&vm, nft_contract, token_id, vm.msg_sender());
 
     // Cannot create auction with zero reserve price
     assert!(contract.create_auction(
@@ -89,31 +127,21 @@ fn test_place_bid() {
     let (vm, mut contract) = setup();
     contract.initialize(U256::from(500)).unwrap();
 
-    let nft_contract = Address::from([1u8; 20]);
-    let token_id = U256::from(1);
-    let reserve_price = U256::from(1000);
-    let duration = U256::from(3600);
-
     // Create auction
-    let auction_id = contract.create_auction(
-        nft_contract,
-        token_id,
-        reserve_price,
-        duration
-    ).unwrap();
+    let auction_id = create_mocked_auction(&vm, &mut contract, Address::from([1u8; 20]), U256::from(1));
 
     // Set different bidder
     let bidder = Address::from([2u8; 20]);
-    vm.set_msg_sender(bidder);
-    vm.set_msg_value(U256::from(1500));
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(1500));
 
     // Place bid
     assert!(contract.place_bid(auction_id).is_ok());
 
     // Check bid details
     let auction = contract.get_auction(auction_id).unwrap();
-    assert_eq!(auction.4, bidder); // highest_bidder
-    assert_eq!(auction.5, U256::from(1500)); // highest_bid
+    assert_eq!(auction.4, U256::from(1500)); // highest_bid
+    assert_eq!(auction.5, bidder); // highest_bidder
 }
 
 #[test]
@@ -121,30 +149,20 @@ fn test_invalid_bids() {
     let (vm, mut contract) = setup();
     contract.initialize(U256::from(500)).unwrap();
 
-    let nft_contract = Address::from([1u8; 20]);
-    let token_id = U256::from(1);
-    let reserve_price = U256::from(1000);
-    let duration = U256::from(3600);
-
-    // Create auction
-    let auction_id = contract.create_auction(
-        nft_contract,
-        token_id,
-        reserve_price,
-        duration
-    ).unwrap();
+    let seller = vm.msg_sender();
+    let auction_id = create_mocked_auction(&vm, &mut contract, Address::from([1u8; 20]), U256::from(1));
 
     let bidder = Address::from([2u8; 20]);
-    vm.set_msg_sender(bidder);
+    vm.set_sender(bidder);
 
     // Bid below reserve price should fail
-    vm.set_msg_value(U256::from(500));
+    vm.set_value(U256::from(500));
     assert!(contract.place_bid(auction_id).is_err());
 
     // Seller cannot bid on own auction
-    vm.set_msg_sender(vm.msg_sender()); // Reset to original sender (seller)
-    vm.set_msg_value(U256::from(1500));
-    assert!(contract.place_bid(auction_id).is_err());
+    vm.set_sender(seller);
+    vm.set_value(U256::from(1500));
+    assert!(matches!(contract.place_bid(auction_id), Err(MarketplaceError::SellerCannotBid(_))));
 }
 
 #[test]
@@ -152,40 +170,29 @@ fn test_bid_progression() {
     let (vm, mut contract) = setup();
     contract.initialize(U256::from(500)).unwrap();
 
-    let nft_contract = Address::from([1u8; 20]);
-    let token_id = U256::from(1);
-    let reserve_price = U256::from(1000);
-    let duration = U256::from(3600);
-
-    // Create auction
-    let auction_id = contract.create_auction(
-        nft_contract,
-        token_id,
-        reserve_price,
-        duration
-    ).unwrap();
+    let auction_id = create_mocked_auction(&vm, &mut contract, Address::from([1u8; 20]), U256::from(1));
 
     // First bidder
     let bidder1 = Address::from([2u8; 20]);
-    vm.set_msg_sender(bidder1);
-    vm.set_msg_value(U256::from(1500));
+    vm.set_sender(bidder1);
+    vm.set_value(U256::from(1500));
     assert!(contract.place_bid(auction_id).is_ok());
 
     // Second bidder with higher bid
     let bidder2 = Address::from([3u8; 20]);
-    vm.set_msg_sender(bidder2);
-    vm.set_msg_value(U256::from(2000));
+    vm.set_sender(bidder2);
+    vm.set_value(U256::from(2000));
     assert!(contract.place_bid(auction_id).is_ok());
 
     // Check highest bidder changed
     let auction = contract.get_auction(auction_id).unwrap();
-    assert_eq!(auction.4, bidder2); // highest_bidder
-    assert_eq!(auction.5, U256::from(2000)); // highest_bid
+    assert_eq!(auction.4, U256::from(2000)); // highest_bid
+    assert_eq!(auction.5, bidder2); // highest_bidder
 
     // Cannot bid lower than current highest bid
     let bidder3 = Address::from([4u8; 20]);
-    vm.set_msg_sender(bidder3);
-    vm.set_msg_value(U256::from(1800));
+    vm.set_sender(bidder3);
+    vm.set_value(U256::from(1800));
     assert!(contract.place_bid(auction_id).is_err());
 }
 
@@ -194,34 +201,23 @@ fn test_settle_auction() {
     let (vm, mut contract) = setup();
     contract.initialize(U256::from(500)).unwrap();
 
-    let nft_contract = Address::from([1u8; 20]);
-    let token_id = U256::from(1);
-    let reserve_price = U256::from(1000);
-    let duration = U256::from(3600);
-
-    // Create auction
-    let auction_id = contract.create_auction(
-        nft_contract,
-        token_id,
-        reserve_price,
-        duration
-    ).unwrap();
+    let auction_id = create_mocked_auction(&vm, &mut contract, Address::from([1u8; 20]), U256::from(1));
 
     // Place bid
     let bidder = Address::from([2u8; 20]);
-    vm.set_msg_sender(bidder);
-    vm.set_msg_value(U256::from(1500));
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(1500));
     contract.place_bid(auction_id).unwrap();
 
     // Advance time past auction end
-    vm.set_block_timestamp(vm.block_timestamp() + duration + U256::from(1));
+    vm.set_block_timestamp(vm.block_timestamp() + 3600 + 1);
 
     // Settle auction
     assert!(contract.settle_auction(auction_id).is_ok());
 
     // Check auction is settled
     let auction = contract.get_auction(auction_id).unwrap();
-    assert_eq!(auction.6, true); // settled
+    assert!(auction.7); // settled
 }
 
 #[test]
@@ -229,18 +225,7 @@ fn test_cannot_settle_active_auction() {
     let (vm, mut contract) = setup();
     contract.initialize(U256::from(500)).unwrap();
 
-    let nft_contract = Address::from([1u8; 20]);
-    let token_id = U256::from(1);
-    let reserve_price = U256::from(1000);
-    let duration = U256::from(3600);
-
-    // Create auction
-    let auction_id = contract.create_auction(
-        nft_contract,
-        token_id,
-        reserve_price,
-        duration
-    ).unwrap();
+    let auction_id = create_mocked_auction(&vm, &mut contract, Address::from([1u8; 20]), U256::from(1));
 
     // Cannot settle active auction
     assert!(contract.settle_auction(auction_id).is_err());
@@ -248,7 +233,7 @@ fn test_cannot_settle_active_auction() {
 
 #[test]
 fn test_nonexistent_auction() {
-    let (vm, mut contract) = setup();
+    let (_vm, mut contract) = setup();
     contract.initialize(U256::from(500)).unwrap();
 
     let nonexistent_auction = U256::from(999);
@@ -264,8 +249,7 @@ fn test_update_platform_fee() {
     let (vm, mut contract) = setup();
     contract.initialize(U256::from(500)).unwrap();
 
-    let original_owner = vm.msg_sender();
-    let new_fee = U256::from(750); // 7.5%
+    let new_fee = U256::from(250); // 2.5%
 
     // Update platform fee
     assert!(contract.update_platform_fee(new_fee).is_ok());
@@ -273,13 +257,13 @@ fn test_update_platform_fee() {
 
     // Non-owner cannot update platform fee
     let non_owner = Address::from([1u8; 20]);
-    vm.set_msg_sender(non_owner);
+    vm.set_sender(non_owner);
     assert!(contract.update_platform_fee(U256::from(1000)).is_err());
 }
 
 #[test]
 fn test_invalid_platform_fee() {
-    let (vm, mut contract) = setup();
+    let (_vm, mut contract) = setup();
 
     // Cannot initialize with fee > 10000 basis points (100%)
     assert!(contract.initialize(U256::from(10001)).is_err());
@@ -289,4 +273,2128 @@ fn test_invalid_platform_fee() {
 
     // Cannot update to invalid fee
     assert!(contract.update_platform_fee(U256::from(10001)).is_err());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_pause_blocks_trading() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    // Only the platform owner can pause
+    let owner = vm.msg_sender();
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(contract.pause().is_err());
+
+    vm.set_sender(owner);
+    assert!(contract.pause().is_ok());
+    assert_eq!(contract.is_paused().ok(), Some(true));
+
+    // Cannot pause twice
+    assert!(contract.pause().is_err());
+
+    // New auctions, bids and settlement are blocked
+    assert!(contract.create_auction(
+        Address::from([1u8; 20]),
+        U256::from(1),
+        U256::from(1000),
+        U256::from(3600)
+    ).is_err());
+    assert!(contract.place_bid(U256::from(1)).is_err());
+    assert!(contract.settle_auction(U256::from(1)).is_err());
+
+    // Unpause restores normal operation
+    assert!(contract.unpause().is_ok());
+    assert_eq!(contract.is_paused().ok(), Some(false));
+    assert!(contract.unpause().is_err());
+}
+
+#[test]
+fn test_emergency_cancel_requires_pause() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    // Not available while the marketplace is running
+    assert!(contract.emergency_cancel_auction(U256::from(1)).is_err());
+
+    assert!(contract.pause().is_ok());
+
+    // Non-owner cannot force-cancel
+    let owner = vm.msg_sender();
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(contract.emergency_cancel_auction(U256::from(1)).is_err());
+
+    // Unknown auction is rejected
+    vm.set_sender(owner);
+    assert!(contract.emergency_cancel_auction(U256::from(999)).is_err());
+}
+
+#[test]
+fn test_emergency_cancel_refunds_bidder() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let owner = vm.msg_sender();
+
+    let nft_contract = Address::from([1u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let bidder = Address::from([7u8; 20]);
+    vm.set_sender(seller);
+    let working = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(1));
+    let broken = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(2));
+
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(working).is_ok());
+    assert!(contract.place_bid(broken).is_ok());
+    vm.set_value(U256::ZERO);
+
+    vm.set_sender(owner);
+    assert!(contract.pause().is_ok());
+
+    // The NFT goes back to the seller and the bid is credited to the bidder
+    mock_delivery(&vm, nft_contract, U256::from(1), seller, true);
+    assert!(contract.emergency_cancel_auction(working).is_ok());
+    let event = assert_last_event::<AuctionEmergencyCanceled>(&vm);
+    assert_eq!((event.auctionId, event.seller, event.bidder, event.refundAmount), (working, seller, bidder, U256::from(2000)));
+    assert_eq!(contract.get_balance(bidder).ok(), Some(U256::from(2000)));
+    assert_eq!(contract.get_nft_claim(working).unwrap().0, Address::ZERO);
+    assert_eq!(contract.is_auction_cancelled(working).ok(), Some(true));
+
+    // A collection that refuses the transfer doesn't hold up the refund: the
+    // seller claims the token once it can move again
+    mock_delivery(&vm, nft_contract, U256::from(2), seller, false);
+    assert!(contract.emergency_cancel_auction(broken).is_ok());
+    assert_last_event::<AuctionEmergencyCanceled>(&vm);
+    assert_eq!(last_event_of::<NftTransferFailed>(&vm).recipient, seller);
+    assert_eq!(contract.get_balance(bidder).ok(), Some(U256::from(4000)));
+    assert_eq!(contract.get_nft_claim(broken).unwrap().0, seller);
+
+    mock_delivery(&vm, nft_contract, U256::from(2), seller, true);
+    assert!(contract.claim_nft(broken).is_ok());
+    assert_eq!(assert_last_event::<NftClaimed>(&vm).recipient, seller);
+}
+
+#[test]
+fn test_two_step_ownership_transfer() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let owner = vm.msg_sender();
+    let new_owner = Address::from([7u8; 20]);
+
+    // Start transfer
+    assert!(contract.transfer_platform_ownership(new_owner).is_ok());
+    assert_eq!(contract.get_pending_platform_owner().ok(), Some(new_owner));
+    assert_eq!(contract.get_platform_owner().ok(), Some(owner));
+
+    // Only the pending owner can accept
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(contract.accept_platform_ownership().is_err());
+
+    vm.set_sender(new_owner);
+    assert!(contract.accept_platform_ownership().is_ok());
+    assert_eq!(contract.get_platform_owner().ok(), Some(new_owner));
+    assert_eq!(contract.get_pending_platform_owner().ok(), Some(Address::ZERO));
+
+    // Previous owner lost admin rights
+    vm.set_sender(owner);
+    assert!(contract.update_platform_fee(U256::from(100)).is_err());
+}
+
+#[test]
+fn test_update_fee_recipient() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    // Defaults to the deployer
+    assert_eq!(contract.get_fee_recipient().ok(), Some(vm.msg_sender()));
+
+    let treasury = Address::from([8u8; 20]);
+    assert!(contract.update_fee_recipient(treasury).is_ok());
+    assert_eq!(contract.get_fee_recipient().ok(), Some(treasury));
+
+    // Zero address is rejected
+    assert!(contract.update_fee_recipient(Address::ZERO).is_err());
+
+    // Non-owner cannot change the recipient
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(contract.update_fee_recipient(treasury).is_err());
+}
+
+
+#[test]
+fn test_fee_increase_is_timelocked() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    // Increase is queued rather than applied
+    assert!(contract.update_platform_fee(U256::from(750)).is_ok());
+    assert_eq!(contract.get_platform_fee_percentage().ok(), Some(U256::from(500)));
+
+    let (pending_fee, execute_after) = contract.get_pending_platform_fee().ok().unwrap();
+    assert_eq!(pending_fee, U256::from(750));
+    assert_eq!(execute_after, U256::from(vm.block_timestamp() + 2 * 86400));
+
+    // Cannot execute before the delay has passed
+    assert!(contract.execute_platform_fee_change().is_err());
+
+    vm.set_block_timestamp(vm.block_timestamp() + 2 * 86400);
+    assert!(contract.execute_platform_fee_change().is_ok());
+    assert_eq!(contract.get_platform_fee_percentage().ok(), Some(U256::from(750)));
+
+    // Nothing left to execute or cancel
+    assert!(contract.execute_platform_fee_change().is_err());
+    assert!(contract.cancel_platform_fee_change().is_err());
+}
+
+#[test]
+fn test_cancel_queued_fee_increase() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    assert!(contract.update_platform_fee(U256::from(1000)).is_ok());
+
+    // Non-owner cannot cancel
+    let owner = vm.msg_sender();
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(contract.cancel_platform_fee_change().is_err());
+
+    vm.set_sender(owner);
+    assert!(contract.cancel_platform_fee_change().is_ok());
+    assert_eq!(contract.get_pending_platform_fee().ok(), Some((U256::ZERO, U256::ZERO)));
+    assert_eq!(contract.get_platform_fee_percentage().ok(), Some(U256::from(500)));
+}
+
+#[test]
+fn test_nft_claim_requires_existing_auction() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nonexistent_auction = U256::from(999);
+
+    // Claim and refund paths reject unknown auctions
+    assert!(contract.get_nft_claim(nonexistent_auction).is_err());
+    assert!(contract.claim_nft(nonexistent_auction).is_err());
+
+    vm.set_sender(Address::from([2u8; 20]));
+    assert!(contract.refund_undeliverable_nft(nonexistent_auction).is_err());
+}
+
+/// Make the marketplace's transfer of `token_id` to `to` revert (or succeed again)
+fn mock_delivery(vm: &TestVM, nft_contract: Address, token_id: U256, to: Address, succeeds: bool) {
+    let transfer = IERC721::transferFromCall { from: vm.contract_address(), to, tokenId: token_id };
+    vm.mock_call(nft_contract, transfer.abi_encode(), if succeeds { Ok(vec![]) } else { Err(vec![]) });
+}
+
+#[test]
+fn test_failed_delivery_can_be_claimed_later() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let seller = vm.msg_sender();
+
+    let nft_contract = Address::from([1u8; 20]);
+    let token_id = U256::from(1);
+    let auction_id = create_mocked_auction(&vm, &mut contract, nft_contract, token_id);
+
+    let winner = Address::from([2u8; 20]);
+    vm.set_sender(winner);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_value(U256::ZERO);
+
+    // The NFT contract refuses the transfer: settlement records a claim and
+    // holds the proceeds
+    mock_delivery(&vm, nft_contract, token_id, winner, false);
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(auction_id).is_ok());
+    let event = assert_last_event::<AuctionSettled>(&vm);
+    assert_eq!(event.nftRecipient, Address::ZERO);
+    assert_eq!(event.sellerProceeds, U256::ZERO);
+    let claim = contract.get_nft_claim(auction_id).unwrap();
+    assert_eq!(claim.0, winner);
+    assert!(claim.2); // proceeds pending
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::ZERO);
+
+    // Once transfers work again, claiming delivers the NFT and releases the proceeds
+    mock_delivery(&vm, nft_contract, token_id, winner, true);
+    assert!(contract.claim_nft(auction_id).is_ok());
+    let event = assert_last_event::<NftClaimed>(&vm);
+    assert_eq!(event.recipient, winner);
+    assert_eq!(event.sellerProceeds, U256::from(1900));
+    assert_eq!(event.platformFee, U256::from(100));
+    // The seller is also the platform owner here, so it receives the fee too
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::from(2000));
+    assert!(matches!(contract.claim_nft(auction_id), Err(MarketplaceError::NothingToClaim(_))));
+}
+
+#[test]
+fn test_undeliverable_nft_refund_after_timeout() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let seller = vm.msg_sender();
+
+    let nft_contract = Address::from([1u8; 20]);
+    let token_id = U256::from(1);
+    let auction_id = create_mocked_auction(&vm, &mut contract, nft_contract, token_id);
+
+    let winner = Address::from([2u8; 20]);
+    vm.set_sender(winner);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_value(U256::ZERO);
+
+    mock_delivery(&vm, nft_contract, token_id, winner, false);
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(auction_id).is_ok());
+
+    // Only the winner, and only after the claim timeout
+    assert!(matches!(contract.refund_undeliverable_nft(auction_id), Err(MarketplaceError::RefundNotAvailable(_))));
+    vm.set_block_timestamp(vm.block_timestamp() + 7 * 86400);
+    vm.set_sender(seller);
+    assert!(matches!(contract.refund_undeliverable_nft(auction_id), Err(MarketplaceError::NotAuctionWinner(_))));
+
+    vm.set_sender(winner);
+    assert!(contract.refund_undeliverable_nft(auction_id).is_ok());
+    let event = assert_last_event::<WinningBidRefunded>(&vm);
+    assert_eq!(event.amount, U256::from(2000));
+    assert_eq!(contract.get_balance(winner).unwrap(), U256::from(2000));
+
+    // The token now belongs back with the seller, without any proceeds
+    let claim = contract.get_nft_claim(auction_id).unwrap();
+    assert_eq!(claim.0, seller);
+    assert!(!claim.2);
+    mock_delivery(&vm, nft_contract, token_id, seller, true);
+    assert!(contract.claim_nft(auction_id).is_ok());
+    assert_eq!(assert_last_event::<NftClaimed>(&vm).sellerProceeds, U256::ZERO);
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::ZERO);
+}
+
+#[test]
+fn test_refund_request_delivers_when_transfer_recovers() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let seller = vm.msg_sender();
+
+    let nft_contract = Address::from([1u8; 20]);
+    let token_id = U256::from(1);
+    let auction_id = create_mocked_auction(&vm, &mut contract, nft_contract, token_id);
+
+    let winner = Address::from([2u8; 20]);
+    vm.set_sender(winner);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_value(U256::ZERO);
+
+    mock_delivery(&vm, nft_contract, token_id, winner, false);
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(auction_id).is_ok());
+
+    // The token can move again by the time the winner asks for the refund:
+    // the sale completes instead
+    mock_delivery(&vm, nft_contract, token_id, winner, true);
+    vm.set_block_timestamp(vm.block_timestamp() + 7 * 86400);
+    assert!(contract.refund_undeliverable_nft(auction_id).is_ok());
+    let event = assert_last_event::<NftClaimed>(&vm);
+    assert_eq!(event.recipient, winner);
+    assert_eq!(event.sellerProceeds, U256::from(1900));
+    assert_eq!(contract.get_balance(winner).unwrap(), U256::ZERO);
+    // The seller is also the fee recipient here
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::from(2000));
+    assert_eq!(contract.get_nft_claim(auction_id).unwrap().0, Address::ZERO);
+}
+
+#[test]
+fn test_refund_denied_when_winner_hook_rejects() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let edition_contract = Address::from([5u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(7), U256::from(3));
+
+    // The winner is a contract whose receiver hook rejects the units
+    let winner = Address::from([2u8; 20]);
+    vm.set_code(winner, vec![0xfe]);
+    vm.set_sender(winner);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_value(U256::ZERO);
+
+    let marketplace = vm.contract_address();
+    let transfer = IERC1155::safeTransferFromCall { from: marketplace, to: winner, id: U256::from(7), value: U256::from(3), data: Vec::new().into() };
+    vm.mock_call(edition_contract, transfer.abi_encode(), Err(vec![]));
+    let hook = IERC1155Receiver::onERC1155ReceivedCall { operator: marketplace, from: marketplace, id: U256::from(7), value: U256::from(3), data: Vec::new().into() };
+    vm.mock_static_call(winner, hook.abi_encode(), Err(vec![]));
+
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(auction_id).is_ok());
+    vm.set_block_timestamp(vm.block_timestamp() + 7 * 86400);
+
+    // The failure is the winner's own, so the bid stays with the sale
+    assert!(matches!(contract.refund_undeliverable_nft(auction_id), Err(MarketplaceError::RefundNotAvailable(_))));
+    assert_eq!(contract.get_balance(winner).unwrap(), U256::ZERO);
+}
+
+#[test]
+fn test_invalid_bundle_auction() {
+    let (_vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+
+    // Empty bundle is rejected
+    assert!(contract.create_bundle_auction(
+        vec![],
+        U256::from(1000),
+        U256::from(3600)
+    ).is_err());
+
+    // Bundle above the size limit is rejected
+    let oversized = (0..51).map(|i| (nft_contract, U256::from(i))).collect();
+    assert!(contract.create_bundle_auction(
+        oversized,
+        U256::from(1000),
+        U256::from(3600)
+    ).is_err());
+
+    // Same reserve and duration rules as single auctions
+    assert!(contract.create_bundle_auction(
+        vec![(nft_contract, U256::from(1)), (nft_contract, U256::from(2))],
+        U256::ZERO,
+        U256::from(3600)
+    ).is_err());
+
+    // Bundle views reject unknown auctions
+    assert!(contract.get_bundle_items(U256::from(999)).is_err());
+    assert!(contract.is_bundle_auction(U256::from(999)).is_err());
+    assert_eq!(contract.get_next_auction_id().ok(), Some(U256::from(1)));
+}
+
+#[test]
+fn test_bundle_auction_lifecycle() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let seller = Address::from([6u8; 20]);
+    let art = Address::from([1u8; 20]);
+    let music = Address::from([3u8; 20]);
+    let items = vec![(art, U256::from(1)), (art, U256::from(2)), (music, U256::from(5))];
+    for &(nft_contract, token_id) in &items {
+        mock_erc721(&vm, nft_contract, token_id, seller);
+    }
+    vm.set_sender(seller);
+    let auction_id = contract.create_bundle_auction(items.clone(), U256::from(1000), U256::from(3600)).unwrap();
+    let event = assert_last_event::<BundleAuctionCreated>(&vm);
+    assert_eq!(event.auctionId, auction_id);
+    assert_eq!(event.seller, seller);
+    assert_eq!(event.nftContracts, vec![art, art, music]);
+    assert_eq!(event.tokenIds, vec![U256::from(1), U256::from(2), U256::from(5)]);
+    assert_eq!(event.platformFeePercentage, U256::from(500));
+    assert_eq!(contract.is_bundle_auction(auction_id).ok(), Some(true));
+    assert_eq!(contract.get_bundle_items(auction_id).unwrap(), items);
+
+    let bidder = Address::from([2u8; 20]);
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_value(U256::ZERO);
+
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(auction_id).is_ok());
+    let event = assert_last_event::<AuctionSettled>(&vm);
+    assert_eq!(event.winner, bidder);
+    assert_eq!(event.nftRecipient, bidder);
+    assert_eq!(event.sellerProceeds, U256::from(1900));
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::from(1900));
+
+    // A bundle without bids can be cancelled, which returns every item
+    vm.set_sender(seller);
+    let auction_id = contract.create_bundle_auction(items.clone(), U256::from(1000), U256::from(3600)).unwrap();
+    assert!(contract.cancel_auction(auction_id).is_ok());
+    assert_eq!(assert_last_event::<AuctionCanceled>(&vm).auctionId, auction_id);
+    assert_eq!(contract.is_auction_cancelled(auction_id).ok(), Some(true));
+
+    // Bundles move atomically: one item the winner can't receive reverts settlement
+    let auction_id = contract.create_bundle_auction(items.clone(), U256::from(1000), U256::from(3600)).unwrap();
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_value(U256::ZERO);
+    mock_delivery(&vm, music, U256::from(5), bidder, false);
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(auction_id).is_err());
+
+    // Every item is escrowed: one failing transfer rejects the bundle
+    vm.set_sender(seller);
+    let escrow = IERC721::transferFromCall { from: seller, to: vm.contract_address(), tokenId: U256::from(5) };
+    vm.mock_call(music, escrow.abi_encode(), Err(vec![]));
+    assert!(matches!(
+        contract.create_bundle_auction(items, U256::from(1000), U256::from(3600)),
+        Err(MarketplaceError::TransferFailed(_))
+    ));
+}
+
+#[test]
+fn test_erc1155_deposit_creates_auction() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let edition_contract = Address::from([5u8; 20]);
+    mock_edition_deposits(&vm, edition_contract);
+    let seller = Address::from([6u8; 20]);
+    let terms = edition_terms();
+
+    // The ERC1155 contract calls the hook on safeTransferFrom
+    vm.set_sender(edition_contract);
+    assert!(contract.on_erc1155_received(seller, seller, U256::from(7), U256::from(25), terms.clone()).is_ok());
+
+    let auction_id = U256::from(1);
+    let auction = contract.get_auction(auction_id).ok().unwrap();
+    assert_eq!(auction.0, edition_contract);
+    assert_eq!(auction.1, U256::from(7));
+    assert_eq!(auction.2, seller);
+    assert_eq!(contract.is_erc1155_auction(auction_id).ok(), Some(true));
+    assert_eq!(contract.get_auction_amount(auction_id).ok(), Some(U256::from(25)));
+
+    // Batch deposit lists every id with the same terms
+    let ids = vec![U256::from(8), U256::from(9)];
+    let values = vec![U256::from(1), U256::from(2)];
+    assert!(contract.on_erc1155_batch_received(seller, seller, ids, values, terms.clone()).is_ok());
+    assert_eq!(contract.get_next_auction_id().ok(), Some(U256::from(4)));
+
+    // Deposits without listing terms or with zero amount are rejected
+    assert!(contract.on_erc1155_received(seller, seller, U256::from(7), U256::from(1), Bytes::from(vec![])).is_err());
+    assert!(contract.on_erc1155_received(seller, seller, U256::from(7), U256::ZERO, terms).is_err());
+}
+
+#[test]
+fn test_order_nonce_cancellation() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let seller = Address::from([6u8; 20]);
+    vm.set_sender(seller);
+
+    // Cancel a single order
+    assert_eq!(contract.is_order_nonce_valid(seller, U256::from(3)).ok(), Some(true));
+    assert!(contract.cancel_order(U256::from(3)).is_ok());
+    assert_eq!(contract.is_order_nonce_valid(seller, U256::from(3)).ok(), Some(false));
+    assert!(contract.cancel_order(U256::from(3)).is_err());
+
+    // Bump the minimum nonce to invalidate everything below it
+    assert!(contract.cancel_all_orders(U256::from(10)).is_ok());
+    assert_eq!(contract.get_min_order_nonce(seller).ok(), Some(U256::from(10)));
+    assert_eq!(contract.is_order_nonce_valid(seller, U256::from(9)).ok(), Some(false));
+    assert_eq!(contract.is_order_nonce_valid(seller, U256::from(10)).ok(), Some(true));
+
+    // Minimum nonce can only increase
+    assert!(contract.cancel_all_orders(U256::from(10)).is_err());
+}
+
+#[test]
+fn test_invalid_order_fulfillment() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let order = Order {
+        seller: Address::from([6u8; 20]),
+        nftContract: Address::from([1u8; 20]),
+        tokenId: U256::from(1),
+        price: U256::from(1000),
+        currency: Address::ZERO,
+        expiry: U256::from(vm.block_timestamp() + 3600),
+        nonce: U256::from(1),
+    };
+
+    vm.set_sender(Address::from([2u8; 20]));
+
+    // Payment must match the order price
+    vm.set_value(U256::from(999));
+    assert!(contract.fulfill_order(order.clone(), Bytes::from(vec![0u8; 65])).is_err());
+
+    // ERC20 currencies are not supported
+    vm.set_value(U256::from(1000));
+    let mut erc20_order = order.clone();
+    erc20_order.currency = Address::from([3u8; 20]);
+    assert!(contract.fulfill_order(erc20_order, Bytes::from(vec![0u8; 65])).is_err());
+
+    // Expired orders are rejected
+    let mut expired_order = order.clone();
+    expired_order.expiry = U256::from(vm.block_timestamp());
+    assert!(contract.fulfill_order(expired_order, Bytes::from(vec![0u8; 65])).is_err());
+
+    // Malformed signatures are rejected
+    assert!(contract.fulfill_order(order, Bytes::from(vec![0u8; 64])).is_err());
+}
+
+/// Sign `digest` with `wallet` and mock the ECRECOVER precompile to return the
+/// address the signature really recovers to
+fn sign_digest(vm: &TestVM, wallet: &ethers::signers::LocalWallet, digest: alloy_primitives::B256) -> stylus_sdk::abi::Bytes {
+    use ethers::types::H256;
+
+    let signature = wallet.sign_hash(H256(digest.0)).unwrap();
+    let signer = Address::from(signature.recover(H256(digest.0)).unwrap().0);
+
+    let bytes = signature.to_vec();
+    let (r, s) = (U256::from_be_slice(&bytes[0..32]), U256::from_be_slice(&bytes[32..64]));
+    let input = (digest, U256::from(bytes[64]), r, s).abi_encode();
+    vm.mock_static_call(Address::with_last_byte(1), input, Ok(signer.abi_encode()));
+
+    bytes.into()
+}
+
+#[test]
+fn test_signed_order_fulfillment() {
+    use ethers::signers::{LocalWallet, Signer};
+
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let owner = vm.msg_sender();
+
+    let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap();
+    let impostor: LocalWallet = "0123456789012345678901234567890123456789012345678901234567890123".parse().unwrap();
+    let seller = Address::from(wallet.address().0);
+    let buyer = Address::from([2u8; 20]);
+    let nft_contract = Address::from([1u8; 20]);
+
+    let order = Order {
+        seller,
+        nftContract: nft_contract,
+        tokenId: U256::from(1),
+        price: U256::from(2000),
+        currency: Address::ZERO,
+        expiry: U256::from(vm.block_timestamp() + 3600),
+        nonce: U256::from(1),
+    };
+    mock_erc721(&vm, nft_contract, U256::from(1), seller);
+    let digest = contract.hash_order(order.clone()).unwrap();
+
+    vm.set_sender(buyer);
+    vm.set_value(U256::from(2000));
+
+    // A signature from anyone but the seller is rejected
+    let forged = sign_digest(&vm, &impostor, digest);
+    assert!(matches!(contract.fulfill_order(order.clone(), forged), Err(MarketplaceError::InvalidSignature(_))));
+
+    // The seller's signature fills the order and splits the payment
+    let signature = sign_digest(&vm, &wallet, digest);
+    assert!(contract.fulfill_order(order.clone(), signature.clone()).is_ok());
+    let event = assert_last_event::<OrderFulfilled>(&vm);
+    assert_eq!(event.orderHash, digest);
+    assert_eq!(event.buyer, buyer);
+    assert_eq!(event.sellerProceeds, U256::from(1900));
+    assert_eq!(event.platformFee, U256::from(100));
+    assert_eq!(contract.get_balance(seller).ok(), Some(U256::from(1900)));
+    assert_eq!(contract.get_balance(owner).ok(), Some(U256::from(100)));
+
+    // The nonce is consumed, so the order cannot be replayed
+    assert_eq!(contract.is_order_nonce_valid(seller, U256::from(1)).ok(), Some(false));
+    assert!(matches!(contract.fulfill_order(order, signature), Err(MarketplaceError::OrderNonceInvalid(_))));
+}
+
+#[test]
+fn test_invalid_listings() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+
+    // Zero price is rejected
+    assert!(contract.create_listing(nft_contract, U256::from(1), U256::ZERO).is_err());
+
+    // Unknown listings cannot be bought or canceled, and report as invalid
+    let nonexistent_listing = U256::from(999);
+    assert!(contract.get_listing(nonexistent_listing).is_err());
+    assert!(contract.cancel_listing(nonexistent_listing).is_err());
+    assert_eq!(contract.is_listing_valid(nonexistent_listing).ok(), Some(false));
+
+    vm.set_sender(Address::from([2u8; 20]));
+    vm.set_value(U256::from(1000));
+    assert!(contract.buy(nonexistent_listing).is_err());
+
+    assert_eq!(contract.get_next_listing_id().ok(), Some(U256::from(1)));
+}
+
+#[test]
+fn test_buy_reverifies_listing() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+    let token_id = U256::from(1);
+    let seller = Address::from([6u8; 20]);
+    let buyer = Address::from([2u8; 20]);
+    let marketplace = vm.contract_address();
+
+    vm.set_sender(seller);
+    mock_erc721(&vm, nft_contract, token_id, seller);
+    let listing_id = contract.create_listing(nft_contract, token_id, U256::from(1000)).unwrap();
+    assert_eq!(contract.is_listing_valid(listing_id).ok(), Some(true));
+
+    vm.set_sender(buyer);
+    vm.set_value(U256::from(1000));
+
+    // The seller moved the token elsewhere
+    mock_erc721(&vm, nft_contract, token_id, Address::from([9u8; 20]));
+    assert_eq!(contract.is_listing_valid(listing_id).ok(), Some(false));
+    assert!(matches!(contract.buy(listing_id), Err(MarketplaceError::NotTokenOwner(_))));
+
+    // The seller has the token back but revoked the approval
+    vm.mock_static_call(nft_contract, IERC721::ownerOfCall { tokenId: token_id }.abi_encode(), Ok(seller.abi_encode()));
+    vm.mock_static_call(nft_contract, IERC721::getApprovedCall { tokenId: token_id }.abi_encode(), Ok(Address::ZERO.abi_encode()));
+    assert_eq!(contract.is_listing_valid(listing_id).ok(), Some(false));
+    assert!(matches!(contract.buy(listing_id), Err(MarketplaceError::NotApprovedForTransfer(_))));
+
+    // Operator approval is enough to make the listing buyable again
+    let approval_for_all = IERC721::isApprovedForAllCall { owner: seller, operator: marketplace };
+    vm.mock_static_call(nft_contract, approval_for_all.abi_encode(), Ok(true.abi_encode()));
+    assert_eq!(contract.is_listing_valid(listing_id).ok(), Some(true));
+    assert!(contract.buy(listing_id).is_ok());
+    assert_eq!(assert_last_event::<ListingSold>(&vm).buyer, buyer);
+    assert_eq!(contract.is_listing_valid(listing_id).ok(), Some(false));
+    assert!(matches!(contract.buy(listing_id), Err(MarketplaceError::ListingNotActive(_))));
+}
+
+fn assert_last_event<E: alloy_sol_types::SolEvent>(vm: &TestVM) -> E {
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.last().expect("no event emitted");
+    assert_eq!(topics[0], E::SIGNATURE_HASH, "expected {}", E::SIGNATURE);
+    E::decode_raw_log(topics.iter().copied(), data, true).expect("event does not decode")
+}
+
+/// Decode the most recent `E` event, wherever it falls in the log
+fn last_event_of<E: alloy_sol_types::SolEvent>(vm: &TestVM) -> E {
+    let logs = vm.get_emitted_logs();
+    let (topics, data) = logs.iter().rev().find(|(topics, _)| topics[0] == E::SIGNATURE_HASH).unwrap_or_else(|| panic!("no {} event", E::SIGNATURE));
+    E::decode_raw_log(topics.iter().copied(), data, true).expect("event does not decode")
+}
+
+#[test]
+fn test_mutators_emit_events() {
+    let (vm, mut contract) = setup();
+    let owner = vm.msg_sender();
+
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let event = assert_last_event::<MarketplaceInitialized>(&vm);
+    assert_eq!(event.platformOwner, owner);
+    assert_eq!(event.platformFeePercentage, U256::from(500));
+
+    // Fee changes
+    assert!(contract.update_platform_fee(U256::from(400)).is_ok());
+    let event = assert_last_event::<PlatformFeeUpdated>(&vm);
+    assert_eq!(event.previousFeePercentage, U256::from(500));
+    assert_eq!(event.newFeePercentage, U256::from(400));
+
+    assert!(contract.update_platform_fee(U256::from(600)).is_ok());
+    assert_last_event::<PlatformFeeChangeQueued>(&vm);
+    assert!(contract.cancel_platform_fee_change().is_ok());
+    assert_last_event::<PlatformFeeChangeCanceled>(&vm);
+
+    assert!(contract.update_platform_fee(U256::from(600)).is_ok());
+    vm.set_block_timestamp(vm.block_timestamp() + 2 * 86400);
+    assert!(contract.execute_platform_fee_change().is_ok());
+    let event = assert_last_event::<PlatformFeeChangeExecuted>(&vm);
+    assert_eq!(event.previousFeePercentage, U256::from(400));
+    assert_eq!(event.newFeePercentage, U256::from(600));
+
+    // Ownership and fee routing
+    let treasury = Address::from([8u8; 20]);
+    assert!(contract.update_fee_recipient(treasury).is_ok());
+    assert_last_event::<FeeRecipientUpdated>(&vm);
+
+    assert!(contract.transfer_platform_ownership(owner).is_ok());
+    assert_last_event::<PlatformOwnershipTransferStarted>(&vm);
+    assert!(contract.accept_platform_ownership().is_ok());
+    assert_last_event::<PlatformOwnershipTransferred>(&vm);
+
+    // Emergency controls
+    assert!(contract.pause().is_ok());
+    assert_last_event::<Paused>(&vm);
+    assert!(contract.unpause().is_ok());
+    assert_last_event::<Unpaused>(&vm);
+
+    // Signed order cancellation
+    let seller = Address::from([6u8; 20]);
+    vm.set_sender(seller);
+    assert!(contract.cancel_order(U256::from(1)).is_ok());
+    assert_last_event::<OrderCanceled>(&vm);
+    assert!(contract.cancel_all_orders(U256::from(5)).is_ok());
+    assert_last_event::<MinOrderNonceUpdated>(&vm);
+
+    // ERC1155 deposit creates an auction
+    let edition_contract = Address::from([5u8; 20]);
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(7), U256::from(3));
+    let event = assert_last_event::<Erc1155AuctionCreated>(&vm);
+    assert_eq!(event.seller, seller);
+    assert_eq!(event.duration, U256::from(3600));
+    assert_eq!(event.platformFeePercentage, U256::from(600));
+
+    assert!(contract.on_erc1155_batch_received(seller, seller, vec![U256::from(8)], vec![U256::from(1)], edition_terms()).is_ok());
+    assert_last_event::<Erc1155AuctionCreated>(&vm);
+
+    // Bids record the outbid bidder and the refund owed to them
+    let bidder1 = Address::from([2u8; 20]);
+    let bidder2 = Address::from([3u8; 20]);
+
+    vm.set_sender(bidder1);
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    let event = assert_last_event::<BidPlaced>(&vm);
+    assert_eq!(event.previousBidder, Address::ZERO);
+    assert_eq!(event.refund, U256::ZERO);
+
+    // bidder1 accepts ETH, so the refund is pushed
+    vm.set_sender(bidder2);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    let event = assert_last_event::<BidPlaced>(&vm);
+    assert_eq!(event.previousBidder, bidder1);
+    assert_eq!(event.refund, U256::from(1000));
+    let event = last_event_of::<OutbidRefundSent>(&vm);
+    assert_eq!((event.auctionId, event.bidder, event.amount), (auction_id, bidder1, U256::from(1000)));
+
+    // Once bidder1 rejects the pushed refund, it is credited for withdrawal
+    vm.set_sender(bidder1);
+    vm.set_value(U256::from(3000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.mock_call(bidder1, vec![], Err(vec![]));
+    vm.set_sender(bidder2);
+    vm.set_value(U256::from(4000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    let event = assert_last_event::<BidPlaced>(&vm);
+    assert_eq!(event.previousBidder, bidder1);
+    assert_eq!(event.refund, U256::from(3000));
+    let event = last_event_of::<OutbidRefundCredited>(&vm);
+    assert_eq!((event.auctionId, event.bidder, event.amount), (auction_id, bidder1, U256::from(3000)));
+
+    // Withdrawal of the credited refund
+    vm.set_balance(vm.contract_address(), U256::from(7000));
+    vm.set_sender(bidder1);
+    vm.set_value(U256::ZERO);
+    assert!(contract.withdraw().is_ok());
+    let event = assert_last_event::<FundsWithdrawn>(&vm);
+    assert_eq!(event.user, bidder1);
+    assert_eq!(event.recipient, bidder1);
+    assert_eq!(event.amount, U256::from(3000));
+    assert_eq!(event.remainingBalance, U256::ZERO);
+
+    // ERC721 auctions: creation, cancellation and settlement
+    let nft_contract = Address::from([1u8; 20]);
+    vm.set_sender(seller);
+    let auction_id = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(1));
+    let event = assert_last_event::<AuctionCreated>(&vm);
+    assert_eq!(event.auctionId, auction_id);
+    assert_eq!(event.seller, seller);
+    assert_eq!(event.reservePrice, U256::from(1000));
+    assert_eq!(event.platformFeePercentage, U256::from(600));
+
+    let canceled_id = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(2));
+    assert!(contract.cancel_auction(canceled_id).is_ok());
+    let event = assert_last_event::<AuctionCanceled>(&vm);
+    assert_eq!(event.auctionId, canceled_id);
+    assert_eq!(event.seller, seller);
+
+    let undelivered_id = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(3));
+    vm.set_sender(bidder2);
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert!(contract.place_bid(undelivered_id).is_ok());
+    vm.set_value(U256::ZERO);
+
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(auction_id).is_ok());
+    let event = assert_last_event::<AuctionSettled>(&vm);
+    assert_eq!(event.winner, bidder2);
+    assert_eq!(event.seller, seller);
+    assert_eq!(event.amount, U256::from(1000));
+    assert_eq!(event.sellerProceeds, U256::from(940));
+    assert_eq!(event.platformFee, U256::from(60));
+    assert_eq!(event.nftRecipient, bidder2);
+
+    // A failed delivery is reported and later claimed
+    let transfer = IERC721::transferFromCall { from: vm.contract_address(), to: bidder2, tokenId: U256::from(3) };
+    vm.mock_call(nft_contract, transfer.abi_encode(), Err(vec![]));
+    assert!(contract.settle_auction(undelivered_id).is_ok());
+    let logs = vm.get_emitted_logs();
+    let (topics, _) = &logs[logs.len() - 2];
+    assert_eq!(topics[0], NftTransferFailed::SIGNATURE_HASH);
+    assert_eq!(assert_last_event::<AuctionSettled>(&vm).nftRecipient, Address::ZERO);
+
+    vm.mock_call(nft_contract, transfer.abi_encode(), Ok(vec![]));
+    assert!(contract.claim_nft(undelivered_id).is_ok());
+    let event = assert_last_event::<NftClaimed>(&vm);
+    assert_eq!(event.auctionId, undelivered_id);
+    assert_eq!(event.recipient, bidder2);
+    assert_eq!(event.sellerProceeds, U256::from(940));
+
+    // Fixed-price listings
+    vm.set_sender(seller);
+    mock_erc721(&vm, nft_contract, U256::from(4), seller);
+    let listing_id = contract.create_listing(nft_contract, U256::from(4), U256::from(500)).unwrap();
+    let event = assert_last_event::<ListingCreated>(&vm);
+    assert_eq!(event.listingId, listing_id);
+    assert_eq!(event.price, U256::from(500));
+
+    vm.set_sender(bidder2);
+    vm.set_value(U256::from(500));
+    assert!(contract.buy(listing_id).is_ok());
+    let event = assert_last_event::<ListingSold>(&vm);
+    assert_eq!(event.buyer, bidder2);
+    assert_eq!(event.seller, seller);
+    assert_eq!(event.sellerProceeds, U256::from(470));
+    assert_eq!(event.platformFee, U256::from(30));
+
+    vm.set_sender(seller);
+    vm.set_value(U256::ZERO);
+    mock_erc721(&vm, nft_contract, U256::from(6), seller);
+    let listing_id = contract.create_listing(nft_contract, U256::from(6), U256::from(500)).unwrap();
+    assert!(contract.cancel_listing(listing_id).is_ok());
+    let event = assert_last_event::<ListingCanceled>(&vm);
+    assert_eq!(event.listingId, listing_id);
+    assert_eq!(event.seller, seller);
+
+    // Bundle auctions list every item
+    let items = vec![(nft_contract, U256::from(10)), (nft_contract, U256::from(11))];
+    for &(nft_contract, token_id) in &items {
+        mock_erc721(&vm, nft_contract, token_id, seller);
+    }
+    let bundle_id = contract.create_bundle_auction(items, U256::from(1000), U256::from(3600)).unwrap();
+    let event = assert_last_event::<BundleAuctionCreated>(&vm);
+    assert_eq!(event.auctionId, bundle_id);
+    assert_eq!(event.tokenIds, vec![U256::from(10), U256::from(11)]);
+    assert_eq!(event.platformFeePercentage, U256::from(600));
+    vm.set_sender(bidder2);
+
+    // Signed orders
+    let wallet: ethers::signers::LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap();
+    let order_seller = Address::from(ethers::signers::Signer::address(&wallet).0);
+    let order = Order {
+        seller: order_seller,
+        nftContract: nft_contract,
+        tokenId: U256::from(5),
+        price: U256::from(800),
+        currency: Address::ZERO,
+        expiry: U256::from(vm.block_timestamp() + 3600),
+        nonce: U256::ZERO,
+    };
+    mock_erc721(&vm, nft_contract, U256::from(5), order_seller);
+    let signature = sign_digest(&vm, &wallet, contract.hash_order(order.clone()).unwrap());
+    vm.set_value(U256::from(800));
+    assert!(contract.fulfill_order(order, signature).is_ok());
+    let event = assert_last_event::<OrderFulfilled>(&vm);
+    assert_eq!(event.seller, order_seller);
+    assert_eq!(event.buyer, bidder2);
+    assert_eq!(event.sellerProceeds, U256::from(752));
+    assert_eq!(event.platformFee, U256::from(48));
+}
+
+#[test]
+fn test_force_cancel_requires_owner() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    // Non-owner cannot force-cancel
+    let owner = vm.msg_sender();
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(matches!(contract.force_cancel_auction(U256::from(1), 1), Err(MarketplaceError::NotPlatformOwner(_))));
+
+    // Available without pausing, but unknown auctions are rejected
+    vm.set_sender(owner);
+    assert!(matches!(contract.force_cancel_auction(U256::from(999), 1), Err(MarketplaceError::AuctionNotFound(_))));
+    assert!(contract.is_auction_cancelled(U256::from(999)).is_err());
+}
+
+#[test]
+fn test_force_cancel_refunds_bidder() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let owner = vm.msg_sender();
+
+    let nft_contract = Address::from([1u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let bidder = Address::from([7u8; 20]);
+    vm.set_sender(seller);
+    let working = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(1));
+    let broken = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(2));
+
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(working).is_ok());
+    assert!(contract.place_bid(broken).is_ok());
+    vm.set_value(U256::ZERO);
+
+    // No pause needed: the NFT goes back to the seller, the bid to the bidder
+    vm.set_sender(owner);
+    mock_delivery(&vm, nft_contract, U256::from(1), seller, true);
+    assert!(contract.force_cancel_auction(working, 3).is_ok());
+    let event = assert_last_event::<AuctionForceCanceled>(&vm);
+    assert_eq!((event.auctionId, event.seller, event.bidder, event.refundAmount), (working, seller, bidder, U256::from(2000)));
+    assert_eq!(event.reason, 3);
+    assert_eq!(contract.get_balance(bidder).ok(), Some(U256::from(2000)));
+    assert_eq!(contract.get_nft_claim(working).unwrap().0, Address::ZERO);
+    assert_eq!(contract.is_auction_cancelled(working).ok(), Some(true));
+    assert!(matches!(contract.force_cancel_auction(working, 3), Err(MarketplaceError::AuctionAlreadySettled(_))));
+
+    // A token that can't be returned is left for the seller to claim
+    mock_delivery(&vm, nft_contract, U256::from(2), seller, false);
+    assert!(contract.force_cancel_auction(broken, 1).is_ok());
+    assert_eq!(assert_last_event::<AuctionForceCanceled>(&vm).reason, 1);
+    assert_eq!(last_event_of::<NftTransferFailed>(&vm).recipient, seller);
+    assert_eq!(contract.get_balance(bidder).ok(), Some(U256::from(4000)));
+    assert_eq!(contract.get_nft_claim(broken).unwrap().0, seller);
+
+    mock_delivery(&vm, nft_contract, U256::from(2), seller, true);
+    vm.set_sender(seller);
+    assert!(contract.claim_nft(broken).is_ok());
+    assert_eq!(assert_last_event::<NftClaimed>(&vm).recipient, seller);
+}
+
+#[test]
+fn test_curated_collections() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let curated = Address::from([1u8; 20]);
+    let unlisted = Address::from([2u8; 20]);
+
+    // Only the owner manages curation
+    let owner = vm.msg_sender();
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(contract.set_curation_enabled(true).is_err());
+    assert!(contract.add_collection(curated).is_err());
+    vm.set_sender(owner);
+
+    assert!(contract.set_curation_enabled(true).is_ok());
+    assert!(contract.add_collection(curated).is_ok());
+    assert_eq!(contract.is_curation_enabled().ok(), Some(true));
+    assert_eq!(contract.is_collection_allowed(curated).ok(), Some(true));
+    assert_eq!(contract.is_collection_allowed(unlisted).ok(), Some(false));
+
+    // Unlisted collections cannot be auctioned or listed
+    let result = contract.create_auction(unlisted, U256::from(1), U256::from(100), U256::from(86400));
+    assert!(matches!(result, Err(MarketplaceError::CollectionNotAllowed(_))));
+    let result = contract.create_listing(unlisted, U256::from(1), U256::from(100));
+    assert!(matches!(result, Err(MarketplaceError::CollectionNotAllowed(_))));
+
+    // Fee overrides are limited to listed collections and the platform fee ceiling
+    assert!(contract.set_collection_fee(unlisted, U256::from(200)).is_err());
+    assert!(contract.set_collection_fee(curated, U256::from(1001)).is_err());
+    assert!(contract.set_collection_fee(curated, U256::from(200)).is_ok());
+    assert_eq!(contract.get_collection_fee(curated).ok(), Some((true, U256::from(200))));
+
+    // Removing a collection drops its override; that raises the fee back to
+    // the platform fee, so it waits out the fee timelock
+    assert!(contract.remove_collection(curated).is_ok());
+    assert_eq!(contract.is_collection_allowed(curated).ok(), Some(false));
+    assert_eq!(contract.get_collection_fee(curated).ok(), Some((true, U256::from(200))));
+    vm.set_block_timestamp(vm.block_timestamp() + 2 * 86400);
+    assert_eq!(contract.get_collection_fee(curated).ok(), Some((false, U256::ZERO)));
+    assert!(contract.remove_collection(curated).is_err());
+}
+
+#[test]
+fn test_fee_tiers_resolve_in_priority_order() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let collection = Address::from([1u8; 20]);
+    let seller = Address::from([2u8; 20]);
+    let other_seller = Address::from([3u8; 20]);
+
+    // Platform fee applies by default
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(500)));
+
+    // Collection override beats the platform fee
+    assert!(contract.add_collection(collection).is_ok());
+    assert!(contract.set_collection_fee(collection, U256::from(300)).is_ok());
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(300)));
+
+    // Seller tier beats both, including a zero fee
+    assert!(contract.set_seller_fee(seller, U256::ZERO).is_ok());
+    assert_eq!(contract.get_seller_fee(seller).ok(), Some((true, U256::ZERO)));
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::ZERO));
+    assert_eq!(contract.effective_fee(collection, other_seller).ok(), Some(U256::from(300)));
+
+    // Setters are bounded by the 10% cap and restricted to the owner
+    assert!(contract.set_seller_fee(seller, U256::from(1001)).is_err());
+    let owner = vm.msg_sender();
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(contract.set_seller_fee(other_seller, U256::ZERO).is_err());
+    assert!(contract.clear_seller_fee(seller).is_err());
+
+    // Clearing the zero tier raises the seller's fee, so it waits out the timelock
+    vm.set_sender(owner);
+    assert!(contract.clear_seller_fee(seller).is_ok());
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::ZERO));
+    vm.set_block_timestamp(vm.block_timestamp() + 2 * 86400);
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(300)));
+}
+
+#[test]
+fn test_fee_ceiling_caps_overrides() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let collection = Address::from([1u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let other_seller = Address::from([7u8; 20]);
+
+    // The ceiling leaves room for the 10% maximum creator share
+    assert!(matches!(contract.set_max_fee_percentage(U256::from(9001)), Err(MarketplaceError::InvalidFeePercentage(_))));
+    assert!(contract.set_max_fee_percentage(U256::from(9000)).is_ok());
+
+    assert!(contract.add_collection(collection).is_ok());
+    assert!(contract.set_collection_fee(collection, U256::from(800)).is_ok());
+    assert!(contract.set_seller_fee(seller, U256::from(9000)).is_ok());
+    vm.set_block_timestamp(vm.block_timestamp() + 2 * 86400);
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(9000)));
+    assert_eq!(contract.effective_fee(collection, other_seller).ok(), Some(U256::from(800)));
+
+    // Lowering the ceiling holds existing overrides to it
+    assert!(contract.set_max_fee_percentage(U256::from(600)).is_ok());
+    assert_eq!(contract.get_seller_fee(seller).ok(), Some((true, U256::from(9000))));
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(600)));
+    assert_eq!(contract.effective_fee(collection, other_seller).ok(), Some(U256::from(600)));
+    assert_eq!(contract.effective_fee(Address::from([2u8; 20]), other_seller).ok(), Some(U256::from(500)));
+
+    // Mixed bundles skip the collection tier but are held to the ceiling too
+    let items = vec![(collection, U256::from(1)), (Address::from([3u8; 20]), U256::from(2))];
+    for &(nft_contract, token_id) in &items {
+        mock_erc721(&vm, nft_contract, token_id, seller);
+    }
+    vm.set_sender(seller);
+    assert!(contract.create_bundle_auction(items, U256::from(1000), U256::from(3600)).is_ok());
+    assert_eq!(assert_last_event::<BundleAuctionCreated>(&vm).platformFeePercentage, U256::from(600));
+}
+
+#[test]
+fn test_fee_override_increases_are_timelocked() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let collection = Address::from([1u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let start = vm.block_timestamp();
+    assert!(contract.add_collection(collection).is_ok());
+
+    // A collection fee above the platform fee is queued, not applied
+    assert!(contract.set_collection_fee(collection, U256::from(800)).is_ok());
+    let event = assert_last_event::<CollectionFeeChangeQueued>(&vm);
+    assert_eq!(event.feePercentage, U256::from(800));
+    assert_eq!(event.executeAfter, U256::from(start + 2 * 86400));
+    assert_eq!(contract.get_collection_fee(collection).ok(), Some((false, U256::ZERO)));
+    assert_eq!(contract.get_pending_collection_fee(collection).ok(), Some((true, U256::from(800), U256::from(start + 2 * 86400))));
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(500)));
+
+    // It takes effect on its own once the delay has passed
+    vm.set_block_timestamp(start + 2 * 86400);
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(800)));
+    assert_eq!(contract.get_collection_fee(collection).ok(), Some((true, U256::from(800))));
+    assert_eq!(contract.get_pending_collection_fee(collection).ok(), Some((false, U256::ZERO, U256::ZERO)));
+
+    // Reductions apply immediately and drop a queued increase
+    assert!(contract.set_collection_fee(collection, U256::from(900)).is_ok());
+    assert_last_event::<CollectionFeeChangeQueued>(&vm);
+    assert!(contract.set_collection_fee(collection, U256::from(200)).is_ok());
+    assert_last_event::<CollectionFeeUpdated>(&vm);
+    assert_eq!(contract.get_pending_collection_fee(collection).ok(), Some((false, U256::ZERO, U256::ZERO)));
+    vm.set_block_timestamp(vm.block_timestamp() + 2 * 86400);
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(200)));
+
+    // Removing a cheap collection returns it to the platform fee only after the delay
+    assert!(contract.remove_collection(collection).is_ok());
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(200)));
+    vm.set_block_timestamp(vm.block_timestamp() + 2 * 86400);
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(500)));
+
+    // Seller tiers follow the same rules
+    assert!(contract.set_seller_fee(seller, U256::from(700)).is_ok());
+    let event = assert_last_event::<SellerFeeChangeQueued>(&vm);
+    assert!(event.hasOverride);
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(500)));
+    assert!(contract.set_seller_fee(seller, U256::from(100)).is_ok());
+    assert_last_event::<SellerFeeUpdated>(&vm);
+    assert_eq!(contract.effective_fee(collection, seller).ok(), Some(U256::from(100)));
+    assert_eq!(contract.get_pending_seller_fee(seller).ok(), Some((false, U256::ZERO, U256::ZERO)));
+}
+
+#[test]
+fn test_fee_payee_configuration() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let treasury = Address::from([1u8; 20]);
+    let ops = Address::from([2u8; 20]);
+
+    // Shares must be non-zero, go to real accounts and sum to 10000
+    let invalid = |result: Result<(), MarketplaceError>| matches!(result, Err(MarketplaceError::InvalidFeePayees(_)));
+    assert!(invalid(contract.set_fee_payees(vec![(treasury, U256::from(7000)), (ops, U256::from(2000))])));
+    assert!(invalid(contract.set_fee_payees(vec![(treasury, U256::from(10000)), (ops, U256::ZERO)])));
+    assert!(invalid(contract.set_fee_payees(vec![(Address::ZERO, U256::from(10000))])));
+    // Shares that would wrap around to exactly 100% are still rejected
+    assert!(invalid(contract.set_fee_payees(vec![(treasury, U256::MAX), (ops, U256::from(10001))])));
+    assert!(invalid(contract.set_fee_payees((0..11).map(|i| (Address::from([i + 1; 20]), U256::from(1))).collect())));
+
+    assert!(contract.set_fee_payees(vec![(treasury, U256::from(7000)), (ops, U256::from(3000))]).is_ok());
+    let event = assert_last_event::<FeePayeesUpdated>(&vm);
+    assert_eq!(event.accounts, vec![treasury, ops]);
+    assert_eq!(event.sharesBps, vec![U256::from(7000), U256::from(3000)]);
+    assert_eq!(contract.get_fee_payees().ok(), Some(vec![(treasury, U256::from(7000)), (ops, U256::from(3000))]));
+
+    // Reconfiguring replaces the previous split; an empty list clears it
+    assert!(contract.set_fee_payees(vec![(ops, U256::from(10000))]).is_ok());
+    assert_eq!(contract.get_fee_payees().ok(), Some(vec![(ops, U256::from(10000))]));
+    assert!(contract.set_fee_payees(Vec::new()).is_ok());
+    assert_eq!(contract.get_fee_payees().ok(), Some(Vec::new()));
+
+    // Only the owner configures the split
+    vm.set_sender(Address::from([9u8; 20]));
+    assert!(contract.set_fee_payees(vec![(ops, U256::from(10000))]).is_err());
+}
+
+#[test]
+fn test_referral_and_creator_configuration() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    // Referral share is capped at half of the platform fee
+    assert!(matches!(contract.set_referral_share(U256::from(5001)), Err(MarketplaceError::InvalidReferralShare(_))));
+    assert!(contract.set_referral_share(U256::from(2000)).is_ok());
+    assert_eq!(contract.get_referral_share().ok(), Some(U256::from(2000)));
+
+    // Creator shares are capped at 10% and need a creator address
+    let nft_contract = Address::from([1u8; 20]);
+    let creator = Address::from([4u8; 20]);
+    let result = contract.create_auction_with_creator(nft_contract, U256::from(1), U256::from(100), U256::from(3600), creator, U256::from(1001));
+    assert!(matches!(result, Err(MarketplaceError::InvalidCreatorShare(_))));
+    let result = contract.create_auction_with_creator(nft_contract, U256::from(1), U256::from(100), U256::from(3600), Address::ZERO, U256::from(100));
+    assert!(matches!(result, Err(MarketplaceError::InvalidCreatorShare(_))));
+
+    // The high bid's referrer is recorded, but self-referrals are ignored
+    let edition_contract = Address::from([5u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(7), U256::from(1));
+    let bidder = Address::from([7u8; 20]);
+    let referrer = Address::from([8u8; 20]);
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid_with_referrer(auction_id, referrer).is_ok());
+    assert_eq!(contract.get_auction_splits(auction_id).ok(), Some((referrer, Address::ZERO, U256::ZERO)));
+
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid_with_referrer(auction_id, bidder).is_ok());
+    assert_eq!(contract.get_auction_splits(auction_id).ok(), Some((Address::ZERO, Address::ZERO, U256::ZERO)));
+}
+
+#[test]
+fn test_referral_and_creator_payouts() {
+    let (vm, mut contract) = setup();
+    let owner = vm.msg_sender();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    assert!(contract.set_referral_share(U256::from(2000)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+    let creator = Address::from([4u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let bidder = Address::from([7u8; 20]);
+    let referrer = Address::from([8u8; 20]);
+
+    vm.set_sender(seller);
+    mock_erc721(&vm, nft_contract, U256::from(1), seller);
+    let auction_id = contract.create_auction_with_creator(nft_contract, U256::from(1), U256::from(1000), U256::from(3600), creator, U256::from(1000)).unwrap();
+
+    // Raising the share after creation doesn't change this auction's split
+    vm.set_sender(owner);
+    assert!(contract.set_referral_share(U256::from(5000)).is_ok());
+
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(10000));
+    assert!(contract.place_bid_with_referrer(auction_id, referrer).is_ok());
+    vm.set_value(U256::ZERO);
+    mock_delivery(&vm, nft_contract, U256::from(1), bidder, true);
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(auction_id).is_ok());
+
+    // 5% fee is 500: the referrer takes 20% of it; the creator takes 10% of the bid
+    let event = last_event_of::<ReferralPaid>(&vm);
+    assert_eq!((event.referrer, event.amount), (referrer, U256::from(100)));
+    assert_eq!(contract.get_balance(referrer).unwrap(), U256::from(100));
+    assert_eq!(contract.get_balance(creator).unwrap(), U256::from(1000));
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::from(8500));
+    assert_eq!(contract.get_balance(owner).unwrap(), U256::from(400));
+
+    // Listings pay the share in force at purchase
+    vm.set_sender(seller);
+    mock_erc721(&vm, nft_contract, U256::from(2), seller);
+    let listing_id = contract.create_listing(nft_contract, U256::from(2), U256::from(2000)).unwrap();
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(2000));
+    assert!(contract.buy_with_referrer(listing_id, referrer).is_ok());
+    let event = last_event_of::<ReferralPaid>(&vm);
+    assert_eq!((event.tokenId, event.amount), (U256::from(2), U256::from(50)));
+    assert_eq!(contract.get_balance(referrer).unwrap(), U256::from(150));
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::from(8500 + 1900));
+    assert_eq!(contract.get_balance(owner).unwrap(), U256::from(450));
+}
+
+#[test]
+fn test_proxy_bidding() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let edition_contract = Address::from([5u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(7), U256::from(1));
+    let alice = Address::from([7u8; 20]);
+    let bob = Address::from([8u8; 20]);
+    let refund_sent = |vm: &TestVM| {
+        let event = last_event_of::<OutbidRefundSent>(vm);
+        (event.bidder, event.amount)
+    };
+    let standing_bid = |contract: &NeonMarketplace| contract.get_auction(auction_id).ok().map(|auction| (auction.4, auction.5));
+
+    // An opening proxy bid only shows the reserve
+    vm.set_sender(alice);
+    vm.set_value(U256::from(5000));
+    assert!(contract.place_proxy_bid(auction_id).is_ok());
+    assert_eq!(standing_bid(&contract), Some((U256::from(1000), alice)));
+
+    // A lower challenger is outbid automatically by one increment and refunded
+    vm.set_sender(bob);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(standing_bid(&contract), Some((U256::from(2100), alice)));
+    assert_eq!(refund_sent(&vm), (bob, U256::from(2000)));
+    let event = last_event_of::<ProxyBidRaised>(&vm);
+    assert_eq!((event.auctionId, event.bidder, event.challenger), (auction_id, alice, bob));
+    assert_eq!((event.amount, event.challengerRefund), (U256::from(2100), U256::from(2000)));
+
+    // A higher proxy takes the lead at one increment over the old ceiling,
+    // and the old leader gets their whole escrow back
+    vm.set_value(U256::from(6000));
+    assert!(contract.place_proxy_bid(auction_id).is_ok());
+    assert_eq!(standing_bid(&contract), Some((U256::from(5250), bob)));
+    assert_eq!(refund_sent(&vm), (alice, U256::from(5000)));
+
+    // The leader can raise their ceiling without moving the price
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_proxy_bid(auction_id).is_ok());
+    assert_eq!(standing_bid(&contract), Some((U256::from(5250), bob)));
+
+    // Matching the ceiling exactly loses to the earlier bidder
+    vm.set_sender(alice);
+    vm.set_value(U256::from(7000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(standing_bid(&contract), Some((U256::from(7000), bob)));
+    assert_eq!(refund_sent(&vm), (alice, U256::from(7000)));
+
+    // A bidder who rejects the push has the refund credited instead
+    vm.set_value(U256::from(9000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(standing_bid(&contract), Some((U256::from(9000), alice)));
+    assert_eq!(refund_sent(&vm), (bob, U256::from(7000)));
+    vm.mock_call(alice, vec![], Err(vec![]));
+    vm.set_sender(bob);
+    vm.set_value(U256::from(10000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(contract.get_balance(alice).ok(), Some(U256::from(9000)));
+    assert_eq!(contract.get_balance(bob).ok(), Some(U256::ZERO));
+}
+
+#[test]
+fn test_hidden_reserve_commitments() {
+    use alloy_primitives::{keccak256, B256};
+
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    // Commitment is keccak256(abi.encode(reserve, salt))
+    let reserve = U256::from(5000);
+    let salt = B256::from([42u8; 32]);
+    let commitment = keccak256((reserve, salt).abi_encode());
+    assert_eq!(contract.hash_reserve(reserve, salt).ok(), Some(commitment));
+
+    // A hidden-reserve auction needs a commitment
+    let nft_contract = Address::from([1u8; 20]);
+    let result = contract.create_auction_with_hidden_reserve(nft_contract, U256::from(1), U256::from(100), U256::from(3600), B256::ZERO);
+    assert!(matches!(result, Err(MarketplaceError::InvalidReservePrice(_))));
+
+    // Unknown auctions cannot be revealed or queried
+    assert!(matches!(contract.reveal_reserve(U256::from(999), reserve, salt), Err(MarketplaceError::AuctionNotFound(_))));
+    assert!(contract.reserve_met(U256::from(999)).is_err());
+
+    // Public reserves are met as soon as a bid lands
+    let edition_contract = Address::from([5u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(7), U256::from(1));
+    assert_eq!(contract.reserve_met(auction_id).ok(), Some(false));
+
+    vm.set_sender(Address::from([7u8; 20]));
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(contract.reserve_met(auction_id).ok(), Some(true));
+
+    // Auctions without a hidden reserve have nothing to reveal
+    vm.set_sender(seller);
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(matches!(contract.reveal_reserve(auction_id, reserve, salt), Err(MarketplaceError::InvalidReserveReveal(_))));
+}
+
+#[test]
+fn test_hidden_reserve_settlement() {
+    use alloy_primitives::{keccak256, B256};
+
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let bidder = Address::from([7u8; 20]);
+    let reserve = U256::from(5000);
+    let salt = B256::from([42u8; 32]);
+    let commitment = keccak256((reserve, salt).abi_encode());
+
+    vm.set_sender(seller);
+    let mut auction_ids = Vec::new();
+    for token_id in [U256::from(1), U256::from(2)] {
+        mock_erc721(&vm, nft_contract, token_id, seller);
+        mock_delivery(&vm, nft_contract, token_id, seller, true);
+        auction_ids.push(contract.create_auction_with_hidden_reserve(nft_contract, token_id, U256::from(100), U256::from(3600), commitment).unwrap());
+    }
+
+    // The bidder leads both auctions below the hidden reserve
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(3000));
+    for &auction_id in &auction_ids {
+        assert!(contract.place_bid(auction_id).is_ok());
+    }
+    vm.set_value(U256::ZERO);
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+
+    // Settlement waits for the reveal
+    for &auction_id in &auction_ids {
+        assert!(matches!(contract.settle_auction(auction_id), Err(MarketplaceError::ReserveNotRevealed(_))));
+    }
+
+    // Revealed above the high bid: the NFT goes back and the escrow is refunded
+    vm.set_sender(seller);
+    assert!(contract.reveal_reserve(auction_ids[0], reserve, salt).is_ok());
+    assert_eq!(contract.reserve_met(auction_ids[0]).ok(), Some(false));
+    assert!(contract.settle_auction(auction_ids[0]).is_ok());
+    let event = assert_last_event::<AuctionSettled>(&vm);
+    assert_eq!((event.auctionId, event.winner, event.nftRecipient), (auction_ids[0], Address::ZERO, seller));
+    assert_eq!(contract.get_balance(bidder).unwrap(), U256::from(3000));
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::ZERO);
+
+    // A seller who never reveals can't hold the bid hostage: once the reveal
+    // window lapses anyone settles and the reserve counts as not met
+    vm.set_sender(Address::from([9u8; 20]));
+    vm.set_block_timestamp(vm.block_timestamp() + 2 * 86400 - 1);
+    assert!(matches!(contract.settle_auction(auction_ids[1]), Err(MarketplaceError::ReserveNotRevealed(_))));
+    vm.set_block_timestamp(vm.block_timestamp() + 1);
+    assert!(contract.settle_auction(auction_ids[1]).is_ok());
+    let event = assert_last_event::<AuctionSettled>(&vm);
+    assert_eq!((event.auctionId, event.winner, event.nftRecipient), (auction_ids[1], Address::ZERO, seller));
+    assert_eq!(contract.get_balance(bidder).unwrap(), U256::from(6000));
+    assert_eq!(contract.get_balance(seller).unwrap(), U256::ZERO);
+}
+
+#[test]
+fn test_merkle_gated_auction() {
+    use alloy_primitives::{keccak256, B256};
+
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let edition_contract = Address::from([5u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(7), U256::from(1));
+
+    // Two-leaf allowlist of holders with OpenZeppelin double-hashed leaves,
+    // hashed as sorted pairs
+    let alice = Address::from([7u8; 20]);
+    let bob = Address::from([8u8; 20]);
+    let carol = Address::from([9u8; 20]);
+    let alice_leaf = keccak256(keccak256(alice.abi_encode()));
+    let bob_leaf = keccak256(keccak256(bob.abi_encode()));
+    let (left, right) = if alice_leaf <= bob_leaf { (alice_leaf, bob_leaf) } else { (bob_leaf, alice_leaf) };
+    let root = keccak256([left.as_slice(), right.as_slice()].concat());
+
+    // Only the seller sets a valid gate
+    vm.set_sender(alice);
+    assert!(matches!(contract.set_auction_gate(auction_id, 1, root, Address::ZERO), Err(MarketplaceError::NotAuctionSeller(_))));
+    vm.set_sender(seller);
+    assert!(matches!(contract.set_auction_gate(auction_id, 7, root, Address::ZERO), Err(MarketplaceError::InvalidGate(_))));
+    assert!(matches!(contract.set_auction_gate(auction_id, 1, B256::ZERO, Address::ZERO), Err(MarketplaceError::InvalidGate(_))));
+    assert!(contract.set_auction_gate(auction_id, 1, root, Address::ZERO).is_ok());
+    assert_eq!(contract.get_auction_gate(auction_id).ok(), Some((1, root, Address::ZERO)));
+
+    // Bidders outside the tree are rejected, even with someone else's proof
+    vm.set_sender(carol);
+    vm.set_value(U256::from(1000));
+    assert!(matches!(contract.place_bid(auction_id), Err(MarketplaceError::BidderNotAllowed(_))));
+    assert!(matches!(contract.place_gated_bid(auction_id, vec![bob_leaf], Bytes::from(vec![])), Err(MarketplaceError::BidderNotAllowed(_))));
+
+    // A valid proof approves the bidder for every bid function
+    vm.set_sender(alice);
+    assert!(contract.authorize_bidder(auction_id, vec![bob_leaf], Bytes::from(vec![])).is_ok());
+    assert_eq!(contract.is_bidder_allowed(auction_id, alice).ok(), Some(true));
+    assert!(contract.place_bid(auction_id).is_ok());
+
+    vm.set_sender(bob);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_gated_bid(auction_id, vec![alice_leaf], Bytes::from(vec![])).is_ok());
+
+    // Gates are fixed once bidding starts
+    vm.set_sender(seller);
+    assert!(matches!(contract.set_auction_gate(auction_id, 2, B256::ZERO, Address::ZERO), Err(MarketplaceError::AuctionHasBids(_))));
+
+    // Re-gating before the first bid drops approvals granted under the old tree
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(8), U256::from(1));
+    vm.set_sender(seller);
+    assert!(contract.set_auction_gate(auction_id, 1, root, Address::ZERO).is_ok());
+    vm.set_sender(alice);
+    assert!(contract.authorize_bidder(auction_id, vec![bob_leaf], Bytes::from(vec![])).is_ok());
+    assert_eq!(contract.is_bidder_allowed(auction_id, alice).ok(), Some(true));
+
+    let bob_only = keccak256([bob_leaf.as_slice(), bob_leaf.as_slice()].concat());
+    vm.set_sender(seller);
+    assert!(contract.set_auction_gate(auction_id, 1, bob_only, Address::ZERO).is_ok());
+    assert_eq!(contract.is_bidder_allowed(auction_id, alice).ok(), Some(false));
+    vm.set_sender(alice);
+    vm.set_value(U256::from(1000));
+    assert!(matches!(contract.place_bid(auction_id), Err(MarketplaceError::BidderNotAllowed(_))));
+
+    // Switching the gate back doesn't revive them either
+    vm.set_sender(seller);
+    assert!(contract.set_auction_gate(auction_id, 1, root, Address::ZERO).is_ok());
+    assert_eq!(contract.is_bidder_allowed(auction_id, alice).ok(), Some(false));
+}
+
+#[test]
+fn test_signature_and_holder_gates() {
+    use alloy_primitives::B256;
+    use ethers::signers::{LocalWallet, Signer};
+
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let signer: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap();
+    let impostor: LocalWallet = "0123456789012345678901234567890123456789012345678901234567890123".parse().unwrap();
+    assert!(contract.set_bid_signer(Address::from(signer.address().0)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+    let membership = Address::from([3u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let alice = Address::from([7u8; 20]);
+    let bob = Address::from([8u8; 20]);
+
+    // Gates are validated and in force from creation, before any bid can land
+    vm.set_sender(seller);
+    mock_erc721(&vm, nft_contract, U256::from(1), seller);
+    mock_erc721(&vm, nft_contract, U256::from(2), seller);
+    let result = contract.create_gated_auction(nft_contract, U256::from(1), U256::from(1000), U256::from(3600), 3, B256::ZERO, Address::ZERO);
+    assert!(matches!(result, Err(MarketplaceError::InvalidGate(_))));
+    let signed_id = contract.create_gated_auction(nft_contract, U256::from(1), U256::from(1000), U256::from(3600), 2, B256::ZERO, Address::ZERO).unwrap();
+    let event = assert_last_event::<AuctionGateSet>(&vm);
+    assert_eq!((event.auctionId, event.gateKind), (signed_id, 2));
+    let holder_id = contract.create_gated_auction(nft_contract, U256::from(2), U256::from(1000), U256::from(3600), 3, B256::ZERO, membership).unwrap();
+    assert_eq!(contract.get_auction_gate(holder_id).ok(), Some((3, B256::ZERO, membership)));
+
+    // Signature gate: only the bid signer's authorization for this bidder counts
+    vm.set_sender(alice);
+    vm.set_value(U256::from(1000));
+    assert!(matches!(contract.place_bid(signed_id), Err(MarketplaceError::BidderNotAllowed(_))));
+    let digest = contract.hash_bid_authorization(signed_id, alice).unwrap();
+    let forged = sign_digest(&vm, &impostor, digest);
+    assert!(matches!(contract.place_gated_bid(signed_id, vec![], forged), Err(MarketplaceError::BidderNotAllowed(_))));
+    let authorization = sign_digest(&vm, &signer, digest);
+    // The authorization names alice, so it doesn't recover to the signer for bob
+    vm.set_sender(bob);
+    assert!(contract.authorize_bidder(signed_id, vec![], authorization.clone()).is_err());
+    vm.set_sender(alice);
+    assert!(contract.place_gated_bid(signed_id, vec![], authorization).is_ok());
+    assert_eq!(contract.is_bidder_allowed(signed_id, alice).ok(), Some(true));
+
+    // Holder gate: balanceOf is checked on every bid
+    let holds = |owner: Address, balance: u64| {
+        vm.mock_static_call(membership, IERC721::balanceOfCall { owner }.abi_encode(), Ok(U256::from(balance).abi_encode()));
+    };
+    holds(alice, 1);
+    holds(bob, 0);
+    assert!(contract.place_bid(holder_id).is_ok());
+    vm.set_sender(bob);
+    vm.set_value(U256::from(2000));
+    assert_eq!(contract.is_bidder_allowed(holder_id, bob).ok(), Some(false));
+    assert!(matches!(contract.place_bid(holder_id), Err(MarketplaceError::BidderNotAllowed(_))));
+    holds(bob, 2);
+    assert!(contract.place_gated_bid(holder_id, vec![], Bytes::from(vec![])).is_ok());
+
+    // The gate is fixed once created auctions have bids
+    vm.set_sender(seller);
+    assert!(matches!(contract.set_auction_gate(holder_id, 0, B256::ZERO, Address::ZERO), Err(MarketplaceError::AuctionHasBids(_))));
+}
+
+#[test]
+fn test_blocklists() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let owner = vm.msg_sender();
+
+    let edition_contract = Address::from([5u8; 20]);
+    mock_edition_deposits(&vm, edition_contract);
+    let seller = Address::from([6u8; 20]);
+    let flagged = Address::from([7u8; 20]);
+    let bidder = Address::from([8u8; 20]);
+    let terms = edition_terms();
+
+    // Only the owner manages blocklists
+    vm.set_sender(flagged);
+    assert!(contract.set_account_blocked(flagged, false).is_err());
+    vm.set_sender(owner);
+    assert!(contract.set_account_blocked(flagged, true).is_ok());
+    assert!(contract.set_token_blocked(edition_contract, U256::from(13), true).is_ok());
+    assert_eq!(contract.is_account_blocked(flagged).ok(), Some(true));
+    assert_eq!(contract.is_token_blocked(edition_contract, U256::from(13)).ok(), Some(true));
+
+    // Blocked sellers and tokens cannot be listed
+    vm.set_sender(edition_contract);
+    let result = contract.on_erc1155_received(flagged, flagged, U256::from(7), U256::from(1), terms.clone());
+    assert!(matches!(result, Err(MarketplaceError::AccountBlocked(_))));
+    let result = contract.on_erc1155_received(seller, seller, U256::from(13), U256::from(1), terms);
+    assert!(matches!(result, Err(MarketplaceError::TokenBlocked(_))));
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(7), U256::from(1));
+
+    // Blocked bidders cannot bid
+    vm.set_sender(flagged);
+    vm.set_value(U256::from(1000));
+    assert!(matches!(contract.place_bid(auction_id), Err(MarketplaceError::AccountBlocked(_))));
+
+    // Escrow from before a block is frozen: settlement waits for the owner
+    // and the account cannot withdraw
+    vm.set_sender(bidder);
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_sender(owner);
+    assert!(contract.set_account_blocked(bidder, true).is_ok());
+
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(matches!(contract.settle_auction(auction_id), Err(MarketplaceError::AccountBlocked(_))));
+    vm.set_sender(bidder);
+    assert!(matches!(contract.withdraw(), Err(MarketplaceError::AccountBlocked(_))));
+}
+
+#[test]
+fn test_blocked_lots_stay_in_escrow() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let owner = vm.msg_sender();
+
+    let nft_contract = Address::from([1u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let bidder = Address::from([7u8; 20]);
+    let challenger = Address::from([8u8; 20]);
+    vm.set_sender(seller);
+    let reported = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(1));
+    let other = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(2));
+
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(reported).is_ok());
+
+    // Blocked tokens and sellers stop taking bids
+    vm.set_sender(owner);
+    vm.set_value(U256::ZERO);
+    assert!(contract.set_token_blocked(nft_contract, U256::from(1), true).is_ok());
+    assert!(contract.set_account_blocked(seller, true).is_ok());
+    vm.set_sender(challenger);
+    vm.set_value(U256::from(3000));
+    assert!(matches!(contract.place_bid(reported), Err(MarketplaceError::TokenBlocked(_))));
+    assert!(matches!(contract.place_bid(other), Err(MarketplaceError::AccountBlocked(_))));
+    vm.set_value(U256::ZERO);
+
+    // Force-cancelling refunds the bidder but the token stays in escrow
+    vm.set_sender(owner);
+    assert!(contract.set_account_blocked(seller, false).is_ok());
+    assert!(contract.force_cancel_auction(reported, 1).is_ok());
+    assert_last_event::<AuctionForceCanceled>(&vm);
+    assert_eq!(contract.get_balance(bidder).ok(), Some(U256::from(2000)));
+    assert_eq!(contract.get_nft_claim(reported).unwrap().0, seller);
+
+    // The seller gets it back only once the token is unblocked
+    vm.set_sender(seller);
+    assert!(matches!(contract.claim_nft(reported), Err(MarketplaceError::TokenBlocked(_))));
+    vm.set_sender(owner);
+    assert!(contract.set_token_blocked(nft_contract, U256::from(1), false).is_ok());
+    vm.set_sender(seller);
+    assert!(contract.claim_nft(reported).is_ok());
+    assert_eq!(assert_last_event::<NftClaimed>(&vm).recipient, seller);
+}
+
+#[test]
+fn test_batch_auction_creation_validates_inputs() {
+    let (_vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+    let reserve = U256::from(1000);
+    let duration = U256::from(3600);
+
+    // Empty batches are rejected
+    let result = contract.create_auctions(nft_contract, vec![], vec![], vec![]);
+    assert!(matches!(result, Err(MarketplaceError::InvalidListingData(_))));
+
+    // Arrays must line up
+    let result = contract.create_auctions(
+        nft_contract,
+        vec![U256::from(1), U256::from(2)],
+        vec![reserve],
+        vec![duration, duration],
+    );
+    assert!(matches!(result, Err(MarketplaceError::InvalidListingData(_))));
+
+    // At most 50 auctions per call
+    let token_ids: Vec<U256> = (1..=51).map(U256::from).collect();
+    let result = contract.create_auctions(nft_contract, token_ids, vec![reserve; 51], vec![duration; 51]);
+    assert!(matches!(result, Err(MarketplaceError::InvalidListingData(_))));
+
+    // Nothing was created
+    assert_eq!(contract.get_next_auction_id().ok(), Some(U256::from(1)));
+}
+
+#[test]
+fn test_batch_auction_creation() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let marketplace = vm.contract_address();
+    let batch = |ids: &[u64]| (ids.iter().map(|&id| U256::from(id)).collect::<Vec<_>>(), vec![U256::from(1000); ids.len()], vec![U256::from(3600); ids.len()]);
+    let approval_for_all = IERC721::isApprovedForAllCall { owner: seller, operator: marketplace }.abi_encode();
+
+    vm.set_sender(seller);
+    for token_id in 1..=5u64 {
+        mock_erc721(&vm, nft_contract, U256::from(token_id), seller);
+    }
+    mock_erc721(&vm, nft_contract, U256::from(9), Address::from([9u8; 20]));
+
+    // A reverting isApprovedForAll means the marketplace isn't approved
+    vm.mock_static_call(nft_contract, approval_for_all.clone(), Err(vec![]));
+    let (ids, reserves, durations) = batch(&[1, 2]);
+    assert!(matches!(contract.create_auctions(nft_contract, ids, reserves, durations), Err(MarketplaceError::NotApprovedForTransfer(_))));
+
+    // Every token is escrowed under its own auction
+    vm.mock_static_call(nft_contract, approval_for_all, Ok(true.abi_encode()));
+    let (ids, reserves, durations) = batch(&[1, 2, 3]);
+    let auction_ids = contract.create_auctions(nft_contract, ids, reserves, durations).unwrap();
+    assert_eq!(auction_ids, vec![U256::from(1), U256::from(2), U256::from(3)]);
+    for (auction_id, token_id) in auction_ids.into_iter().zip(1..=3u64) {
+        let auction = contract.get_auction(auction_id).unwrap();
+        assert_eq!((auction.0, auction.1, auction.2), (nft_contract, U256::from(token_id), seller));
+    }
+    let event = assert_last_event::<AuctionCreated>(&vm);
+    assert_eq!(event.auctionId, U256::from(3));
+
+    // A bad later entry rejects the whole batch before anything is opened
+    let logged = vm.get_emitted_logs().len();
+    let (ids, reserves, durations) = batch(&[4, 9]);
+    assert!(matches!(contract.create_auctions(nft_contract, ids, reserves, durations), Err(MarketplaceError::NotTokenOwner(_))));
+    let (ids, reserves, durations) = batch(&[4, 5, 4]);
+    assert!(matches!(contract.create_auctions(nft_contract, ids, reserves, durations), Err(MarketplaceError::InvalidListingData(_))));
+    assert_eq!(contract.get_next_auction_id().ok(), Some(U256::from(4)));
+    assert_eq!(vm.get_emitted_logs().len(), logged);
+    assert!(contract.get_auction(U256::from(4)).is_err());
+}
+
+#[test]
+fn test_token_collection_info() {
+    use alloy_primitives::FixedBytes;
+
+    let (vm, contract) = setup();
+    let creator = Address::from([4u8; 20]);
+    let info = |nft_contract, token_id: u64| {
+        let info = contract.get_token_collection_info(nft_contract, U256::from(token_id)).unwrap();
+        (info.source, info.collectionId, info.name, info.symbol, info.creator, info.contractURI)
+    };
+    let interface_id = IMultiCollectionNFT::tokenCollectionCall::SELECTOR
+        .iter()
+        .zip(IMultiCollectionNFT::getCollectionCall::SELECTOR)
+        .zip(IMultiCollectionNFT::balanceOfCollectionCall::SELECTOR)
+        .map(|((a, b), c)| a ^ b ^ c)
+        .collect::<Vec<_>>();
+    let supports_multi_collection = IERC165::supportsInterfaceCall { interfaceId: FixedBytes::from_slice(&interface_id) }.abi_encode();
+    let mock_metadata = |nft_contract| {
+        vm.set_code(nft_contract, vec![0xfe]);
+        vm.mock_static_call(nft_contract, IERC721Metadata::nameCall {}.abi_encode(), Ok("Neon".to_string().abi_encode()));
+        vm.mock_static_call(nft_contract, IERC721Metadata::symbolCall {}.abi_encode(), Ok("NEON".to_string().abi_encode()));
+    };
+    let mock_lookup = |nft_contract, token_id: u64, result: Result<Vec<u8>, Vec<u8>>| {
+        vm.mock_static_call(nft_contract, IMultiCollectionNFT::tokenCollectionCall { tokenId: U256::from(token_id) }.abi_encode(), result);
+    };
+
+    // Accounts without code are not asked anything
+    let eoa = Address::from([1u8; 20]);
+    assert_eq!(info(eoa, 1), (0, U256::ZERO, "Unknown Collection".to_string(), "UNK".to_string(), Address::ZERO, String::new()));
+
+    // A multi-collection NFT without ERC-165 is recognized by a successful lookup
+    let multi = Address::from([2u8; 20]);
+    mock_metadata(multi);
+    mock_lookup(multi, 1, Ok(U256::from(3).abi_encode()));
+    let collection = ("Genesis".to_string(), "GEN".to_string(), creator, "ipfs://genesis".to_string(), U256::from(100));
+    vm.mock_static_call(multi, IMultiCollectionNFT::getCollectionCall { collectionId: U256::from(3) }.abi_encode(), Ok(collection.abi_encode_params()));
+    vm.mock_static_call(multi, IERC7572::contractURICall {}.abi_encode(), Ok("ipfs://contract".to_string().abi_encode()));
+    assert_eq!(info(multi, 1), (1, U256::from(3), "Genesis".to_string(), "GEN".to_string(), creator, "ipfs://contract".to_string()));
+
+    // Without an ERC-165 claim, any failed lookup means plain ERC721, even a
+    // revert with a reason
+    mock_lookup(multi, 2, Err(b"unknown token".to_vec()));
+    assert_eq!(info(multi, 2), (2, U256::ZERO, "Neon".to_string(), "NEON".to_string(), Address::ZERO, "ipfs://contract".to_string()));
+
+    // A contract declaring the interface reports failed lookups as such, even
+    // a revert without data
+    let declared = Address::from([3u8; 20]);
+    mock_metadata(declared);
+    vm.mock_static_call(declared, supports_multi_collection, Ok(true.abi_encode()));
+    mock_lookup(declared, 1, Err(vec![]));
+    assert_eq!(info(declared, 1), (3, U256::ZERO, "Neon".to_string(), "NEON".to_string(), Address::ZERO, String::new()));
+
+    // A contract with code but no metadata gets placeholders
+    let bare = Address::from([5u8; 20]);
+    vm.set_code(bare, vec![0xfe]);
+    assert_eq!(info(bare, 1).0, 0);
+}
+
+#[test]
+fn test_marketplace_config() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let owner = vm.msg_sender();
+
+    // Defaults match the previous hard-coded limits
+    assert_eq!(
+        contract.get_config().ok(),
+        Some((U256::from(1), U256::from(30 * 86400), U256::from(1000), U256::from(500), U256::ZERO))
+    );
+
+    // Only the owner changes parameters
+    let stranger = Address::from([9u8; 20]);
+    vm.set_sender(stranger);
+    assert!(contract.set_min_bid_increment(U256::from(1000)).is_err());
+    vm.set_sender(owner);
+
+    // Setters are validated
+    assert!(matches!(contract.set_duration_bounds(U256::ZERO, U256::from(3600)), Err(MarketplaceError::InvalidDuration(_))));
+    assert!(matches!(contract.set_duration_bounds(U256::from(7200), U256::from(3600)), Err(MarketplaceError::InvalidDuration(_))));
+    assert!(matches!(contract.set_duration_bounds(U256::from(1), U256::MAX), Err(MarketplaceError::InvalidDuration(_))));
+    assert!(matches!(contract.set_duration_bounds(U256::from(1), U256::from(360 * 86400 + 1)), Err(MarketplaceError::InvalidDuration(_))));
+    assert!(contract.set_duration_bounds(U256::from(1), U256::from(360 * 86400)).is_ok());
+    assert!(matches!(contract.set_max_fee_percentage(U256::from(400)), Err(MarketplaceError::InvalidFeePercentage(_))));
+    assert!(matches!(contract.set_max_fee_percentage(U256::from(10001)), Err(MarketplaceError::InvalidFeePercentage(_))));
+    assert!(matches!(contract.set_min_bid_increment(U256::ZERO), Err(MarketplaceError::InvalidBidIncrement(_))));
+    assert!(matches!(contract.set_extension_window(U256::from(86401)), Err(MarketplaceError::InvalidExtensionWindow(_))));
+
+    assert!(contract.set_duration_bounds(U256::from(600), U256::from(7200)).is_ok());
+    assert!(contract.set_max_fee_percentage(U256::from(2000)).is_ok());
+    assert!(contract.set_min_bid_increment(U256::from(1000)).is_ok());
+    assert!(contract.set_extension_window(U256::from(300)).is_ok());
+    assert_eq!(
+        contract.get_config().ok(),
+        Some((U256::from(600), U256::from(7200), U256::from(2000), U256::from(1000), U256::from(300)))
+    );
+
+    // The fee ceiling applies to platform fee changes
+    assert!(contract.update_platform_fee(U256::from(300)).is_ok());
+    assert!(matches!(contract.update_platform_fee(U256::from(2001)), Err(MarketplaceError::InvalidFeePercentage(_))));
+
+    // Durations outside the new bounds are rejected
+    let edition_contract = Address::from([5u8; 20]);
+    mock_edition_deposits(&vm, edition_contract);
+    let seller = Address::from([6u8; 20]);
+    vm.set_sender(edition_contract);
+    let short = Bytes::from((U256::from(1000), U256::from(300)).abi_encode());
+    let result = contract.on_erc1155_received(seller, seller, U256::from(1), U256::from(1), short);
+    assert!(matches!(result, Err(MarketplaceError::InvalidDuration(_))));
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(1), U256::from(1));
+
+    // Outbids need the configured 10% increment
+    let first = Address::from([7u8; 20]);
+    let second = Address::from([8u8; 20]);
+    vm.set_sender(first);
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_sender(second);
+    vm.set_value(U256::from(1050));
+    assert!(matches!(contract.place_bid(auction_id), Err(MarketplaceError::BidTooLow(_))));
+
+    // A bid inside the extension window pushes the end back
+    let end_time = contract.get_auction(auction_id).ok().map(|a| a.6).unwrap_or_default();
+    let now = end_time.to::<u64>() - 60;
+    vm.set_block_timestamp(now);
+    vm.set_value(U256::from(1100));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(contract.get_auction(auction_id).ok().map(|a| a.6), Some(U256::from(now + 300)));
+    let event = last_event_of::<AuctionExtended>(&vm);
+    assert_eq!((event.auctionId, event.newEndTime), (auction_id, U256::from(now + 300)));
+}
+
+#[test]
+fn test_batched_auction_views() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let edition_contract = Address::from([5u8; 20]);
+    mock_edition_deposits(&vm, edition_contract);
+    let seller = Address::from([6u8; 20]);
+    let bidder = Address::from([7u8; 20]);
+    let terms = edition_terms();
+
+    vm.set_sender(edition_contract);
+    for token_id in 1..=3u64 {
+        assert!(contract.on_erc1155_received(seller, seller, U256::from(token_id), U256::from(1), terms.clone()).is_ok());
+    }
+
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(U256::from(2)).is_ok());
+
+    // Ranges start at auction 1 and stop at the last auction
+    let page = contract.get_auctions_range(U256::ZERO, U256::from(10)).ok().unwrap_or_default();
+    assert_eq!(page.len(), 3);
+    assert_eq!(page[0].auctionId, U256::from(1));
+    assert_eq!(page[0].status, 0); // active
+    assert_eq!(page[0].minNextBid, U256::from(1000)); // reserve
+    assert_eq!(page[0].timeRemaining, U256::from(3600));
+    assert_eq!(page[1].currentBidder, bidder);
+    assert_eq!(page[1].minNextBid, U256::from(2100)); // 5% over the current bid
+    assert!(page[2].isErc1155);
+    assert_eq!(page[2].amount, U256::from(1));
+
+    // Ranges far past the last id are empty rather than overflowing
+    let page = contract.get_auctions_range(U256::MAX, U256::from(100)).ok();
+    assert_eq!(page.map(|page| page.len()), Some(0));
+
+    // Explicit ids keep their order
+    let auctions = contract.get_auctions(vec![U256::from(3), U256::from(1)]).ok().unwrap_or_default();
+    assert_eq!(auctions.len(), 2);
+    assert_eq!(auctions[0].auctionId, U256::from(3));
+    assert_eq!(auctions[1].auctionId, U256::from(1));
+
+    // ERC721 lots report one unit, like getAuctionAmount
+    let nft_contract = Address::from([1u8; 20]);
+    vm.set_sender(seller);
+    let single = create_mocked_auction(&vm, &mut contract, nft_contract, U256::from(1));
+    let view = contract.get_auctions(vec![single]).ok().unwrap_or_default();
+    assert_eq!(view[0].amount, U256::from(1));
+    assert_eq!(contract.get_auction_amount(single).ok(), Some(U256::from(1)));
+
+    // Unknown ids and oversized pages are rejected
+    assert!(matches!(contract.get_auctions(vec![U256::from(9)]), Err(MarketplaceError::AuctionNotFound(_))));
+    assert!(matches!(contract.get_auctions_range(U256::from(1), U256::from(101)), Err(MarketplaceError::PageTooLarge(_))));
+
+    // Ended auctions report no time left
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    let page = contract.get_auctions_range(U256::from(1), U256::from(1)).ok().unwrap_or_default();
+    assert_eq!(page[0].status, 1); // ended, awaiting settlement
+    assert_eq!(page[0].timeRemaining, U256::ZERO);
+}
+
+#[test]
+fn test_partial_withdrawal_to_recipient() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+    let owner = vm.msg_sender();
+
+    let edition_contract = Address::from([5u8; 20]);
+    mock_edition_deposits(&vm, edition_contract);
+    let seller = Address::from([6u8; 20]);
+    let bidder1 = Address::from([7u8; 20]);
+    let bidder2 = Address::from([8u8; 20]);
+    let vault = Address::from([9u8; 20]);
+
+    // Give bidder1 a 1000 wei refund balance
+    vm.mock_call(bidder1, vec![], Err(vec![]));
+    create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(1), U256::from(1));
+    vm.set_sender(bidder1);
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid(U256::from(1)).is_ok());
+    vm.set_sender(bidder2);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(U256::from(1)).is_ok());
+
+    // bidder2 accepts ETH, so being outbid leaves no balance behind
+    vm.set_sender(Address::from([4u8; 20]));
+    vm.set_value(U256::from(3000));
+    assert!(contract.place_bid(U256::from(1)).is_ok());
+    assert_eq!(last_event_of::<OutbidRefundSent>(&vm).bidder, bidder2);
+    assert_eq!(contract.get_balance(bidder2).ok(), Some(U256::ZERO));
+    vm.set_balance(vm.contract_address(), U256::from(3000));
+    vm.set_value(U256::ZERO);
+
+    // Invalid requests
+    vm.set_sender(bidder1);
+    assert!(matches!(contract.withdraw_to(Address::ZERO, U256::from(100)), Err(MarketplaceError::InvalidRecipient(_))));
+    assert!(matches!(contract.withdraw_to(vault, U256::ZERO), Err(MarketplaceError::InvalidWithdrawalAmount(_))));
+    assert!(matches!(contract.withdraw_to(vault, U256::from(1001)), Err(MarketplaceError::InsufficientBalance(_))));
+
+    // Part of the balance goes to another address
+    assert!(contract.withdraw_to(vault, U256::from(400)).is_ok());
+    let event = assert_last_event::<FundsWithdrawn>(&vm);
+    assert_eq!(event.user, bidder1);
+    assert_eq!(event.recipient, vault);
+    assert_eq!(event.amount, U256::from(400));
+    assert_eq!(event.remainingBalance, U256::from(600));
+    assert_eq!(contract.get_balance(bidder1).ok(), Some(U256::from(600)));
+
+    // Blocklisted recipients can't be paid
+    vm.set_sender(owner);
+    assert!(contract.set_account_blocked(vault, true).is_ok());
+    vm.set_sender(bidder1);
+    assert!(matches!(contract.withdraw_to(vault, U256::from(100)), Err(MarketplaceError::AccountBlocked(_))));
+
+    // The rest is withdrawn normally
+    assert!(contract.withdraw().is_ok());
+    assert_eq!(contract.get_balance(bidder1).ok(), Some(U256::ZERO));
+}
+
+#[test]
+fn test_outbid_refunds_pushed_with_pull_fallback() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let edition_contract = Address::from([5u8; 20]);
+    mock_edition_deposits(&vm, edition_contract);
+    let seller = Address::from([6u8; 20]);
+    let wallet = Address::from([7u8; 20]);
+    let rejecting = Address::from([8u8; 20]);
+    let bidder = Address::from([9u8; 20]);
+    let auction_id = create_edition_auction(&vm, &mut contract, edition_contract, seller, U256::from(1), U256::from(1));
+    let emitted = |vm: &TestVM, signature_hash| vm.get_emitted_logs().iter().filter(|(topics, _)| topics[0] == signature_hash).count();
+
+    // A bidder that accepts ETH is refunded directly
+    vm.set_sender(wallet);
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    vm.set_sender(rejecting);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(emitted(&vm, OutbidRefundSent::SIGNATURE_HASH), 1);
+    assert_eq!(contract.get_balance(wallet).ok(), Some(U256::ZERO));
+    let event = assert_last_event::<BidPlaced>(&vm);
+    assert_eq!(event.refund, U256::from(1000));
+
+    // A bidder whose refund reverts has it credited for withdrawal instead
+    vm.mock_call(rejecting, vec![], Err(vec![]));
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(3000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(emitted(&vm, OutbidRefundCredited::SIGNATURE_HASH), 1);
+    assert_eq!(contract.get_balance(rejecting).ok(), Some(U256::from(2000)));
+}
+
+#[test]
+fn test_erc1155_deposits_are_verified() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let edition_contract = Address::from([5u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let operator = Address::from([7u8; 20]);
+    let terms = edition_terms();
+    let id = U256::from(20);
+    let mock_escrowed = |units: u64| {
+        let balance_of = IERC1155::balanceOfCall { account: vm.contract_address(), id };
+        vm.mock_static_call(edition_contract, balance_of.abi_encode(), Ok(U256::from(units).abi_encode()));
+    };
+
+    // Accounts without code cannot fake a deposit
+    vm.set_sender(seller);
+    let result = contract.on_erc1155_received(seller, seller, id, U256::from(5), terms.clone());
+    assert!(matches!(result, Err(MarketplaceError::InvalidDeposit(_))));
+
+    // Only the owner's own transfer lists, never an operator's or the marketplace's
+    vm.set_code(edition_contract, vec![0xfe]);
+    vm.set_sender(edition_contract);
+    mock_escrowed(5);
+    let result = contract.on_erc1155_received(operator, seller, id, U256::from(5), terms.clone());
+    assert!(matches!(result, Err(MarketplaceError::InvalidDeposit(_))));
+    let marketplace = vm.contract_address();
+    let result = contract.on_erc1155_received(marketplace, marketplace, id, U256::from(5), terms.clone());
+    assert!(matches!(result, Err(MarketplaceError::InvalidDeposit(_))));
+
+    // The marketplace's balance must cover every escrowed unit
+    let result = contract.on_erc1155_received(seller, seller, id, U256::from(6), terms.clone());
+    assert!(matches!(result, Err(MarketplaceError::InvalidDeposit(_))));
+    assert!(contract.on_erc1155_received(seller, seller, id, U256::from(5), terms.clone()).is_ok());
+    let result = contract.on_erc1155_received(seller, seller, id, U256::from(3), terms.clone());
+    assert!(matches!(result, Err(MarketplaceError::InvalidDeposit(_))));
+    let result = contract.on_erc1155_batch_received(seller, seller, vec![id, id], vec![U256::from(2), U256::from(1)], terms.clone());
+    assert!(matches!(result, Err(MarketplaceError::InvalidDeposit(_))));
+    mock_escrowed(8);
+    assert!(contract.on_erc1155_batch_received(seller, seller, vec![id, id], vec![U256::from(2), U256::from(1)], terms.clone()).is_ok());
+
+    // Returning units to the seller releases them from escrow
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+    assert!(contract.settle_auction(U256::from(1)).is_ok());
+    mock_escrowed(3);
+    vm.set_sender(edition_contract);
+    assert!(contract.on_erc1155_received(seller, seller, id, U256::from(0), terms.clone()).is_err());
+    let result = contract.on_erc1155_received(seller, seller, id, U256::from(1), terms);
+    assert!(matches!(result, Err(MarketplaceError::InvalidDeposit(_))));
+}
+
+#[test]
+fn test_erc1155_listings() {
+    let (vm, mut contract) = setup();
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let edition_contract = Address::from([5u8; 20]);
+    let seller = Address::from([6u8; 20]);
+    let buyer = Address::from([7u8; 20]);
+    let id = U256::from(3);
+    let marketplace = vm.contract_address();
+    let mock_holding = |units: u64, approved: bool| {
+        vm.mock_static_call(edition_contract, IERC1155::balanceOfCall { account: seller, id }.abi_encode(), Ok(U256::from(units).abi_encode()));
+        vm.mock_static_call(edition_contract, IERC1155::isApprovedForAllCall { account: seller, operator: marketplace }.abi_encode(), Ok(approved.abi_encode()));
+    };
+
+    vm.set_sender(seller);
+    mock_holding(10, false);
+    let result = contract.create_erc1155_listing(edition_contract, id, U256::from(4), U256::from(1000));
+    assert!(matches!(result, Err(MarketplaceError::NotApprovedForTransfer(_))));
+    mock_holding(10, true);
+    let result = contract.create_erc1155_listing(edition_contract, id, U256::from(11), U256::from(1000));
+    assert!(matches!(result, Err(MarketplaceError::NotTokenOwner(_))));
+    let result = contract.create_erc1155_listing(edition_contract, id, U256::ZERO, U256::from(1000));
+    assert!(matches!(result, Err(MarketplaceError::InvalidAmount(_))));
+
+    let listing_id = contract.create_erc1155_listing(edition_contract, id, U256::from(4), U256::from(1000)).unwrap();
+    let event = assert_last_event::<Erc1155ListingCreated>(&vm);
+    assert_eq!(event.amount, U256::from(4));
+    assert_eq!(contract.is_erc1155_listing(listing_id).ok(), Some(true));
+    assert_eq!(contract.get_listing_amount(listing_id).ok(), Some(U256::from(4)));
+    assert_eq!(contract.is_listing_valid(listing_id).ok(), Some(true));
+
+    // Selling units elsewhere makes the listing stale
+    mock_holding(3, true);
+    assert_eq!(contract.is_listing_valid(listing_id).ok(), Some(false));
+    vm.set_sender(buyer);
+    vm.set_value(U256::from(1000));
+    assert!(matches!(contract.buy(listing_id), Err(MarketplaceError::NotTokenOwner(_))));
+
+    // The buyer receives the listed units and the seller is paid
+    mock_holding(10, true);
+    assert!(contract.buy(listing_id).is_ok());
+    let event = assert_last_event::<ListingSold>(&vm);
+    assert_eq!(event.sellerProceeds, U256::from(950));
+    assert_eq!(contract.get_balance(seller).ok(), Some(U256::from(950)));
+    assert_eq!(contract.is_listing_valid(listing_id).ok(), Some(false));
+
+    // A rejected transfer reverts the purchase
+    vm.set_sender(seller);
+    vm.set_value(U256::ZERO);
+    let listing_id = contract.create_erc1155_listing(edition_contract, id, U256::from(2), U256::from(1000)).unwrap();
+    let transfer = IERC1155::safeTransferFromCall { from: seller, to: buyer, id, value: U256::from(2), data: Vec::new().into() };
+    vm.mock_call(edition_contract, transfer.abi_encode(), Err(vec![]));
+    vm.set_sender(buyer);
+    vm.set_value(U256::from(1000));
+    assert!(matches!(contract.buy(listing_id), Err(MarketplaceError::TransferFailed(_))));
+}
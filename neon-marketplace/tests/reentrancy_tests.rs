@@ -0,0 +1,294 @@
+mod common;
+
+use common::mock_erc721;
+use neon_marketplace::*;
+use stylus_sdk::testing::*;
+use stylus_sdk::stylus_core::calls::{errors::Error, CallAccess, MutatingCallContext, StaticCallContext, ValueTransfer};
+use stylus_sdk::stylus_core::deploy::DeploymentAccess;
+use alloy_primitives::{Address, B256, U256};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Reentry = Box<dyn FnOnce(&TestVM)>;
+
+/// TestVM host whose next mutating call to `target` runs a callback into the
+/// marketplace first, the way a malicious NFT or bidder contract would.
+/// Storage is shared with the wrapped TestVM, so the callback sees the lock
+/// held by the outer call.
+#[derive(Clone)]
+struct ReentrantVM {
+    vm: TestVM,
+    reentry: Rc<RefCell<Option<(Address, Reentry)>>>,
+}
+
+impl ReentrantVM {
+    fn new() -> Self {
+        Self { vm: TestVM::default(), reentry: Rc::new(RefCell::new(None)) }
+    }
+
+    /// Re-enter from `target` when the marketplace next calls it
+    fn on_next_call(&self, target: Address, reentry: impl FnOnce(&TestVM) + 'static) {
+        *self.reentry.borrow_mut() = Some((target, Box::new(reentry)));
+    }
+}
+
+impl Host for ReentrantVM {}
+
+impl CallAccess for ReentrantVM {
+    fn static_call(&self, context: &dyn StaticCallContext, to: Address, data: &[u8]) -> Result<Vec<u8>, Error> {
+        self.vm.static_call(context, to, data)
+    }
+
+    unsafe fn delegate_call(&self, context: &dyn MutatingCallContext, to: Address, data: &[u8]) -> Result<Vec<u8>, Error> {
+        self.vm.delegate_call(context, to, data)
+    }
+
+    fn call(&self, context: &dyn MutatingCallContext, to: Address, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let armed = matches!(&*self.reentry.borrow(), Some((target, _)) if *target == to);
+        if armed {
+            let (_, reentry) = self.reentry.borrow_mut().take().unwrap();
+            let sender = self.vm.msg_sender();
+            self.vm.set_sender(to);
+            reentry(&self.vm);
+            self.vm.set_sender(sender);
+        }
+        self.vm.call(context, to, data)
+    }
+}
+
+impl CryptographyAccess for ReentrantVM {
+    fn native_keccak256(&self, input: &[u8]) -> B256 {
+        self.vm.native_keccak256(input)
+    }
+}
+
+impl CalldataAccess for ReentrantVM {
+    fn read_args(&self, len: usize) -> Vec<u8> {
+        self.vm.read_args(len)
+    }
+    fn read_return_data(&self, offset: usize, size: Option<usize>) -> Vec<u8> {
+        self.vm.read_return_data(offset, size)
+    }
+    fn return_data_size(&self) -> usize {
+        self.vm.return_data_size()
+    }
+    fn write_result(&self, data: &[u8]) {
+        self.vm.write_result(data)
+    }
+}
+
+unsafe impl UnsafeDeploymentAccess for ReentrantVM {
+    unsafe fn create1(&self, code: *const u8, code_len: usize, endowment: *const u8, contract: *mut u8, revert_data_len: *mut usize) {
+        self.vm.create1(code, code_len, endowment, contract, revert_data_len)
+    }
+    unsafe fn create2(&self, code: *const u8, code_len: usize, endowment: *const u8, salt: *const u8, contract: *mut u8, revert_data_len: *mut usize) {
+        self.vm.create2(code, code_len, endowment, salt, contract, revert_data_len)
+    }
+}
+
+impl StorageAccess for ReentrantVM {
+    fn storage_load_bytes32(&self, key: U256) -> B256 {
+        self.vm.storage_load_bytes32(key)
+    }
+    unsafe fn storage_cache_bytes32(&self, key: U256, value: B256) {
+        self.vm.storage_cache_bytes32(key, value)
+    }
+    fn flush_cache(&self, clear: bool) {
+        self.vm.flush_cache(clear)
+    }
+}
+
+unsafe impl UnsafeCallAccess for ReentrantVM {
+    unsafe fn call_contract(&self, to: *const u8, data: *const u8, data_len: usize, value: *const u8, gas: u64, outs_len: &mut usize) -> u8 {
+        self.vm.call_contract(to, data, data_len, value, gas, outs_len)
+    }
+    unsafe fn static_call_contract(&self, to: *const u8, data: *const u8, data_len: usize, gas: u64, outs_len: &mut usize) -> u8 {
+        self.vm.static_call_contract(to, data, data_len, gas, outs_len)
+    }
+    unsafe fn delegate_call_contract(&self, to: *const u8, data: *const u8, data_len: usize, gas: u64, outs_len: &mut usize) -> u8 {
+        self.vm.delegate_call_contract(to, data, data_len, gas, outs_len)
+    }
+}
+
+impl BlockAccess for ReentrantVM {
+    fn block_basefee(&self) -> U256 {
+        self.vm.block_basefee()
+    }
+    fn block_coinbase(&self) -> Address {
+        self.vm.block_coinbase()
+    }
+    fn block_number(&self) -> u64 {
+        self.vm.block_number()
+    }
+    fn block_timestamp(&self) -> u64 {
+        self.vm.block_timestamp()
+    }
+    fn block_gas_limit(&self) -> u64 {
+        self.vm.block_gas_limit()
+    }
+}
+
+impl ChainAccess for ReentrantVM {
+    fn chain_id(&self) -> u64 {
+        self.vm.chain_id()
+    }
+}
+
+impl AccountAccess for ReentrantVM {
+    fn balance(&self, account: Address) -> U256 {
+        self.vm.balance(account)
+    }
+    fn contract_address(&self) -> Address {
+        self.vm.contract_address()
+    }
+    fn code(&self, account: Address) -> Vec<u8> {
+        self.vm.code(account)
+    }
+    fn code_size(&self, account: Address) -> usize {
+        self.vm.code_size(account)
+    }
+    fn code_hash(&self, account: Address) -> B256 {
+        self.vm.code_hash(account)
+    }
+}
+
+impl MemoryAccess for ReentrantVM {
+    fn pay_for_memory_grow(&self, pages: u16) {
+        self.vm.pay_for_memory_grow(pages)
+    }
+}
+
+impl MessageAccess for ReentrantVM {
+    fn msg_sender(&self) -> Address {
+        self.vm.msg_sender()
+    }
+    fn msg_reentrant(&self) -> bool {
+        self.vm.msg_reentrant()
+    }
+    fn msg_value(&self) -> U256 {
+        self.vm.msg_value()
+    }
+    fn tx_origin(&self) -> Address {
+        self.vm.tx_origin()
+    }
+}
+
+impl MeteringAccess for ReentrantVM {
+    fn evm_gas_left(&self) -> u64 {
+        self.vm.evm_gas_left()
+    }
+    fn evm_ink_left(&self) -> u64 {
+        self.vm.evm_ink_left()
+    }
+    fn tx_gas_price(&self) -> U256 {
+        self.vm.tx_gas_price()
+    }
+    fn tx_ink_price(&self) -> u32 {
+        self.vm.tx_ink_price()
+    }
+}
+
+impl DeploymentAccess for ReentrantVM {
+    unsafe fn deploy(&self, code: &[u8], endowment: U256, salt: Option<B256>) -> Result<Address, Vec<u8>> {
+        self.vm.deploy(code, endowment, salt)
+    }
+}
+
+impl LogAccess for ReentrantVM {
+    fn emit_log(&self, input: &[u8], num_topics: usize) {
+        self.vm.emit_log(input, num_topics)
+    }
+    fn raw_log(&self, topics: &[B256], data: &[u8]) -> Result<(), &'static str> {
+        self.vm.raw_log(topics, data)
+    }
+}
+
+impl ValueTransfer for ReentrantVM {
+    fn transfer_eth(&self, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        self.vm.transfer_eth(to, amount)
+    }
+}
+
+/// Arm `host` so the next call to `target` re-enters with `reentry` and
+/// returns whether the inner call was rejected by the lock
+fn reenter_on_next_call(host: &ReentrantVM, target: Address, reentry: impl FnOnce(&mut NeonMarketplace) -> bool + 'static) -> Rc<RefCell<Option<bool>>> {
+    let rejected = Rc::new(RefCell::new(None));
+    let outcome = rejected.clone();
+    host.on_next_call(target, move |vm| {
+        let mut contract = NeonMarketplace::from(vm);
+        *outcome.borrow_mut() = Some(reentry(&mut contract));
+    });
+    rejected
+}
+
+fn reentrancy_rejected<T>(result: Result<T, MarketplaceError>) -> bool {
+    matches!(result, Err(MarketplaceError::Reentrancy(_)))
+}
+
+#[test]
+fn test_malicious_nft_cannot_reenter_settlement() {
+    let host = ReentrantVM::new();
+    let vm = host.vm.clone();
+    let mut contract = NeonMarketplace::from(&host);
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+    let seller = vm.msg_sender();
+    let bidder = Address::from([2u8; 20]);
+    mock_erc721(&vm, nft_contract, U256::from(1), seller);
+    mock_erc721(&vm, nft_contract, U256::from(2), seller);
+    let auction_id = contract.create_auction(nft_contract, U256::from(1), U256::from(1000), U256::from(3600)).unwrap();
+    let other_auction = contract.create_auction(nft_contract, U256::from(2), U256::from(1000), U256::from(3600)).unwrap();
+
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert!(contract.place_bid(other_auction).is_ok());
+    vm.set_value(U256::ZERO);
+    vm.set_block_timestamp(vm.block_timestamp() + 3600);
+
+    // The NFT contract calls back into the marketplace from transferFrom
+    let rejected = reenter_on_next_call(&host, nft_contract, move |marketplace| {
+        reentrancy_rejected(marketplace.settle_auction(auction_id))
+            && reentrancy_rejected(marketplace.settle_auction(other_auction))
+            && reentrancy_rejected(marketplace.claim_nft(auction_id))
+            && reentrancy_rejected(marketplace.withdraw())
+    });
+    assert!(contract.settle_auction(auction_id).is_ok());
+    assert_eq!(*rejected.borrow(), Some(true));
+
+    // Views stay available, and the lock is released once the outer call returns
+    assert!(contract.get_auction(auction_id).unwrap().7);
+    assert!(contract.settle_auction(other_auction).is_ok());
+    assert!(matches!(contract.settle_auction(auction_id), Err(MarketplaceError::AuctionAlreadySettled(_))));
+}
+
+#[test]
+fn test_outbid_bidder_cannot_reenter_refund() {
+    let host = ReentrantVM::new();
+    let vm = host.vm.clone();
+    let mut contract = NeonMarketplace::from(&host);
+    assert!(contract.initialize(U256::from(500)).is_ok());
+
+    let nft_contract = Address::from([1u8; 20]);
+    mock_erc721(&vm, nft_contract, U256::from(1), vm.msg_sender());
+    let auction_id = contract.create_auction(nft_contract, U256::from(1), U256::from(1000), U256::from(3600)).unwrap();
+
+    let attacker = Address::from([2u8; 20]);
+    let bidder = Address::from([3u8; 20]);
+    vm.set_sender(attacker);
+    vm.set_value(U256::from(1000));
+    assert!(contract.place_bid(auction_id).is_ok());
+
+    // The outbid refund is pushed to the attacker, which tries to bid again
+    // and to withdraw while the marketplace is still mid-call
+    vm.set_balance(vm.contract_address(), U256::from(3000));
+    let rejected = reenter_on_next_call(&host, attacker, move |marketplace| {
+        reentrancy_rejected(marketplace.place_bid(auction_id)) && reentrancy_rejected(marketplace.withdraw())
+    });
+    vm.set_sender(bidder);
+    vm.set_value(U256::from(2000));
+    assert!(contract.place_bid(auction_id).is_ok());
+    assert_eq!(*rejected.borrow(), Some(true));
+    assert_eq!(contract.get_auction(auction_id).unwrap().5, bidder);
+}